@@ -0,0 +1,166 @@
+//! Machine architecture at runtime, as opposed to the one the app was built for.
+//!
+//! An x64 build on an Apple Silicon Mac runs under Rosetta, and on Windows on
+//! ARM under emulation; the compile-time target can't tell. Executables are
+//! inspected by their headers, so a bundled sidecar can be checked too.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+    X86_64,
+    Aarch64,
+}
+
+impl Arch {
+    /// The architecture the app was compiled for
+    pub fn built() -> Option<Self> {
+        match std::env::consts::ARCH {
+            "x86_64" => Some(Self::X86_64),
+            "aarch64" => Some(Self::Aarch64),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::X86_64 => "x86_64",
+            Self::Aarch64 => "aarch64",
+        }
+    }
+
+    /// Target triple in the form uv's `--python-platform` takes
+    pub fn uv_platform(self) -> &'static str {
+        #[cfg(target_os = "macos")]
+        return match self {
+            Self::X86_64 => "x86_64-apple-darwin",
+            Self::Aarch64 => "aarch64-apple-darwin",
+        };
+        #[cfg(windows)]
+        return match self {
+            Self::X86_64 => "x86_64-pc-windows-msvc",
+            Self::Aarch64 => "aarch64-pc-windows-msvc",
+        };
+        #[cfg(not(any(target_os = "macos", windows)))]
+        return match self {
+            Self::X86_64 => "x86_64-unknown-linux-gnu",
+            Self::Aarch64 => "aarch64-unknown-linux-gnu",
+        };
+    }
+}
+
+/// The architecture of the machine itself, `None` when it isn't one we know
+pub fn native() -> Option<Arch> {
+    #[cfg(target_os = "macos")]
+    {
+        // Also reported to processes running under Rosetta
+        let mut arm64: libc::c_int = 0;
+        let mut size = size_of::<libc::c_int>();
+        // SAFETY: the name is NUL-terminated and `arm64` has room for `size` bytes
+        let status = unsafe {
+            libc::sysctlbyname(
+                c"hw.optional.arm64".as_ptr(),
+                std::ptr::from_mut(&mut arm64).cast(),
+                &mut size,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+        // Intel Macs don't have the key at all
+        if status == 0 && arm64 == 1 {
+            Some(Arch::Aarch64)
+        } else {
+            Some(Arch::X86_64)
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use windows_sys::Win32::System::SystemInformation::{
+            IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64,
+        };
+        use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+        let (mut process, mut machine) = (0, 0);
+        // SAFETY: the pseudo handle needs no closing and both outputs are valid
+        if unsafe { IsWow64Process2(GetCurrentProcess(), &mut process, &mut machine) } == 0 {
+            return Arch::built();
+        }
+        match machine {
+            IMAGE_FILE_MACHINE_AMD64 => Some(Arch::X86_64),
+            IMAGE_FILE_MACHINE_ARM64 => Some(Arch::Aarch64),
+            _ => None,
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", windows)))]
+    Arch::built()
+}
+
+/// Whether the app runs translated, e.g. an x64 build under Rosetta
+pub fn translated() -> bool {
+    native()
+        .zip(Arch::built())
+        .is_some_and(|(native, built)| native != built)
+}
+
+/// Architectures an ELF, Mach-O (including universal) or PE executable
+/// contains; empty when the format or CPU isn't recognized
+pub fn of_binary(path: &Path) -> io::Result<Vec<Arch>> {
+    let mut header = Vec::with_capacity(4096);
+    File::open(path)?.take(4096).read_to_end(&mut header)?;
+    Ok(parse_header(&header))
+}
+
+fn parse_header(header: &[u8]) -> Vec<Arch> {
+    let u16_le = |at: usize| {
+        header
+            .get(at..at + 2)
+            .map(|b| u16::from_le_bytes([b[0], b[1]]))
+    };
+    let u32_le = |at: usize| {
+        let b = header.get(at..at + 4)?;
+        Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let u32_be = |at: usize| {
+        let b = header.get(at..at + 4)?;
+        Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    };
+    let mach = |cpu_type: u32| match cpu_type {
+        0x0100_0007 => Some(Arch::X86_64),
+        0x0100_000c => Some(Arch::Aarch64),
+        _ => None,
+    };
+
+    match header.get(..4) {
+        // ELF, assuming little endian as both CPUs are
+        Some(b"\x7fELF") => match u16_le(18) {
+            Some(0x3e) => vec![Arch::X86_64],
+            Some(0xb7) => vec![Arch::Aarch64],
+            _ => Vec::new(),
+        },
+        // 64-bit Mach-O
+        Some([0xcf, 0xfa, 0xed, 0xfe]) => u32_le(4).and_then(mach).into_iter().collect(),
+        // Universal Mach-O, a list of 20-byte `fat_arch` entries
+        Some([0xca, 0xfe, 0xba, 0xbe]) => {
+            let count = u32_be(4).unwrap_or(0).min(16) as usize;
+            (0..count)
+                .filter_map(|i| u32_be(8 + i * 20).and_then(mach))
+                .collect()
+        }
+        Some([b'M', b'Z', ..]) => {
+            let pe = u32_le(0x3c).unwrap_or(0) as usize;
+            if header.get(pe..pe + 4) != Some(b"PE\0\0") {
+                return Vec::new();
+            }
+            match u16_le(pe + 4) {
+                Some(0x8664) => vec![Arch::X86_64],
+                Some(0xaa64) => vec![Arch::Aarch64],
+                _ => Vec::new(),
+            }
+        }
+        _ => Vec::new(),
+    }
+}
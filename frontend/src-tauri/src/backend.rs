@@ -1,207 +1,4739 @@
 use anyhow::{anyhow, Context, Result};
-use std::fs::{create_dir_all, OpenOptions};
+use chrono::Local;
+use regex::Regex;
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::{self, create_dir_all, OpenOptions};
 use std::io::Write;
-use std::path::PathBuf;
-use std::sync::Mutex;
-use std::time::Duration;
+use std::net::{Ipv4Addr, TcpListener};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicU32, AtomicUsize, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Mutex, MutexGuard, OnceLock, PoisonError};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::async_runtime::Receiver;
 use tauri::path::BaseDirectory;
-use tauri::{AppHandle, Manager};
-use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri::{AppHandle, Emitter, Manager, Runtime, Wry};
+use tauri_plugin_opener::OpenerExt;
+use tauri_plugin_shell::process::{Command, CommandChild, CommandEvent, TerminatedPayload};
 use tauri_plugin_shell::ShellExt;
+use tokio::sync::watch;
+
+use crate::arch::{self, Arch};
+use crate::backend_copy;
+use crate::error::BackendError;
+use crate::log_events::{self, LineBuffer, LogForwarder, LogStream};
+use crate::log_file::{self, RotatingFile};
+use crate::maintenance::MaintenanceSchedule;
+use crate::process_tree;
+use crate::registry::{
+    ProcessHandle, ProcessInfo, ProcessRegistry, ProcessSnapshot,
+    PRIMARY_PROCESS as BACKEND_PROCESS_NAME,
+};
+use crate::settings::{LaunchSettings, ServiceConfig};
+use crate::supervisor::{self, Supervisor};
+
+/// When the manager restarts a backend that died on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum RestartPolicy {
+    /// Leave a dead backend down so its state can be inspected
+    Never,
+    /// Restart after a non-zero exit, at most `max_retries` times in a row
+    OnFailure { max_retries: u32 },
+    /// Restart after any exit, without limit
+    Always,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self::OnFailure { max_retries: 3 }
+    }
+}
+
+impl RestartPolicy {
+    /// Consecutive restarts allowed, `None` meaning unlimited
+    fn retry_limit(&self) -> Option<u32> {
+        match self {
+            Self::Never => Some(0),
+            Self::OnFailure { max_retries } => Some(*max_retries),
+            Self::Always => None,
+        }
+    }
+}
+
+/// Parses `never`, `always`, `on-failure` or `on-failure:<max retries>`
+impl FromStr for RestartPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "never" => Ok(Self::Never),
+            None if s == "always" => Ok(Self::Always),
+            None if s == "on-failure" => Ok(Self::default()),
+            Some(("on-failure", retries)) => retries
+                .parse()
+                .map(|max_retries| Self::OnFailure { max_retries })
+                .map_err(|e| format!("invalid retry count {:?}: {}", retries, e)),
+            _ => Err(format!("unknown restart policy {:?}", s)),
+        }
+    }
+}
+
+impl Display for RestartPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Never => write!(f, "never"),
+            Self::OnFailure { max_retries } => write!(f, "on-failure:{}", max_retries),
+            Self::Always => write!(f, "always"),
+        }
+    }
+}
+
+/// How a starting backend is recognized as ready
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Readiness {
+    /// Wait for the startup banner, then poll the health endpoint. A running
+    /// backend keeps being probed there.
+    #[default]
+    Http,
+    /// Wait for a line of output containing this marker. For backends without a
+    /// health endpoint, which are then never probed over HTTP.
+    LogMarker(String),
+}
+
+/// Parses `http`, `log` (waiting for `Uvicorn running on`) or `log:<marker>`
+impl FromStr for Readiness {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "http" => Ok(Self::Http),
+            None if s == "log" => Ok(Self::LogMarker(DEFAULT_READY_MARKER.to_string())),
+            Some(("log", marker)) if !marker.is_empty() => Ok(Self::LogMarker(marker.to_string())),
+            _ => Err(format!("unknown readiness strategy {:?}", s)),
+        }
+    }
+}
+
+impl Display for Readiness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Http => write!(f, "http"),
+            Self::LogMarker(marker) => write!(f, "log:{}", marker),
+        }
+    }
+}
+
+/// The bundled uv binary picked for this machine
+#[derive(Debug, Clone)]
+struct UvSidecar {
+    /// Sidecar name the shell plugin resolves next to the app executable
+    name: String,
+    /// Passed as `--python-platform` to uv sync when uv runs translated, so
+    /// the interpreter and wheels it picks agree on one architecture
+    python_platform: Option<&'static str>,
+}
+
+/// Which uv runs `uv sync` and the backend; resolved on first use, see `BackendManager::uv`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum UvProgram {
+    /// The bundled sidecar
+    Sidecar(String),
+    /// `uv` found on PATH or the `uv_path` launch setting
+    System(PathBuf),
+}
+
+/// Delays between consecutive automatic restarts
+#[derive(Debug, Clone, Copy)]
+pub struct RestartBackoff {
+    /// Delay before the first restart, doubled for every following attempt
+    pub base_delay: Duration,
+    /// Upper bound for the backoff delay
+    pub max_delay: Duration,
+}
+
+impl Default for RestartBackoff {
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RestartBackoff {
+    fn delay_for(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay)
+    }
+}
+
+/// Readiness probe against the backend HTTP server
+#[derive(Debug, Clone)]
+pub struct HealthCheck {
+    /// Path polled on the backend port, overridable via `VALUECELL_HEALTH_PATH`
+    pub path: String,
+    /// Pause between two probes
+    pub interval: Duration,
+    /// Probes sent before the start is considered failed
+    pub max_attempts: u32,
+    /// Timeout of a single probe
+    pub request_timeout: Duration,
+}
+
+impl Default for HealthCheck {
+    fn default() -> Self {
+        Self {
+            path: std::env::var(HEALTH_PATH_ENV).unwrap_or_else(|_| HEALTH_PATH.to_string()),
+            interval: Duration::from_millis(500),
+            max_attempts: 120,
+            request_timeout: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Periodic health probing of a running backend
+#[derive(Debug, Clone, Copy)]
+pub struct HealthWatchdog {
+    /// Pause between two probes
+    pub interval: Duration,
+    /// Consecutive failed probes before the backend counts as unresponsive
+    pub failure_threshold: u32,
+    /// Restart an unresponsive backend; when off, it is only reported
+    pub auto_restart: bool,
+    /// A backend without output for this long whose health probe fails is
+    /// considered wedged; `None` disables the check
+    pub silence_timeout: Option<Duration>,
+    /// Pause between two checks that the process still exists; `None` disables them
+    pub heartbeat: Option<Duration>,
+}
+
+impl Default for HealthWatchdog {
+    fn default() -> Self {
+        let silence_secs: u64 = env_or(SILENCE_TIMEOUT_ENV, 0);
+        let heartbeat_secs: u64 = env_or(HEARTBEAT_ENV, DEFAULT_HEARTBEAT_SECS);
+        Self {
+            interval: Duration::from_secs(15),
+            failure_threshold: 3,
+            auto_restart: env_or(WATCHDOG_RESTART_ENV, true),
+            silence_timeout: (silence_secs > 0).then(|| Duration::from_secs(silence_secs)),
+            heartbeat: (heartbeat_secs > 0).then(|| Duration::from_secs(heartbeat_secs)),
+        }
+    }
+}
+
+/// Memory ceiling of the backend process tree; off unless a limit is configured
+#[derive(Debug, Clone, Copy)]
+pub struct MemoryWatchdog {
+    /// Pause between two samples
+    pub interval: Duration,
+    /// Above this the frontend is warned, once per crossing
+    pub warn_bytes: Option<u64>,
+    /// Above this the backend is restarted and an incident report written
+    pub limit_bytes: Option<u64>,
+}
+
+impl Default for MemoryWatchdog {
+    fn default() -> Self {
+        let megabytes = |name| {
+            let mb: u64 = env_or(name, 0);
+            (mb > 0).then(|| mb * 1024 * 1024)
+        };
+        Self {
+            interval: Duration::from_secs(30),
+            warn_bytes: megabytes(MEMORY_WARN_ENV),
+            limit_bytes: megabytes(MEMORY_LIMIT_ENV),
+        }
+    }
+}
+
+impl MemoryWatchdog {
+    fn enabled(&self) -> bool {
+        self.warn_bytes.is_some() || self.limit_bytes.is_some()
+    }
+}
+
+/// How long a stopping backend gets at each step before it is pushed harder
+#[derive(Debug, Clone, Copy)]
+pub struct ShutdownTimeouts {
+    /// Wait for the backend to acknowledge the quiesce request (stop trading,
+    /// settle in-flight exchange calls) before it is asked to shut down
+    pub quiesce: Duration,
+    /// Wait after the HTTP shutdown request, before any signal is sent
+    pub graceful: Duration,
+    /// Wait after SIGINT (or a plain `taskkill`) before descendants are force-killed
+    pub before_kill: Duration,
+    /// Budget for a whole `stop_all`; whatever is left afterwards is killed outright
+    pub deadline: Duration,
+}
+
+impl Default for ShutdownTimeouts {
+    fn default() -> Self {
+        Self {
+            quiesce: Duration::from_millis(env_or(
+                QUIESCE_TIMEOUT_ENV,
+                DEFAULT_QUIESCE_TIMEOUT.as_millis() as u64,
+            )),
+            graceful: Duration::from_millis(env_or(
+                GRACEFUL_TIMEOUT_ENV,
+                DEFAULT_GRACE_PERIOD.as_millis() as u64,
+            )),
+            before_kill: Duration::from_millis(env_or(
+                KILL_TIMEOUT_ENV,
+                DEFAULT_GRACE_PERIOD.as_millis() as u64,
+            )),
+            deadline: Duration::from_millis(env_or(
+                STOP_DEADLINE_ENV,
+                DEFAULT_STOP_DEADLINE.as_millis() as u64,
+            )),
+        }
+    }
+}
+
+/// Lifecycle state of the backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackendState {
+    Installing,
+    Starting,
+    Running,
+    Stopping,
+    Stopped,
+    Failed,
+}
+
+/// Snapshot returned to the frontend by `get_backend_status`
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendStatus {
+    pub state: BackendState,
+    pub pid: Option<u32>,
+    pub last_error: Option<String>,
+    pub last_exit: Option<TerminatedPayload>,
+    /// `host:port` from the startup banner
+    pub address: Option<String>,
+    /// Set when backend output can't be written to files and only reaches the app log
+    pub log_warning: Option<String>,
+    /// Every registered process, the API server included
+    pub services: Vec<ProcessInfo>,
+}
+
+/// Returned by `get_backend_stats` and mirrored to `stats.json` in the log dir
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendStats {
+    pub pid: Option<u32>,
+    pub uptime_secs: Option<u64>,
+    /// Spawn time of the current process, milliseconds since the Unix epoch
+    pub started_at: Option<u64>,
+    /// Processes spawned since the app was launched
+    pub start_count: u32,
+    /// Spawns after the first one, whether automatic or requested
+    pub restart_count: u32,
+    pub last_exit_code: Option<i32>,
+}
+
+/// Returned by `backend_paths`, for the diagnostics screen
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendPaths {
+    pub backend_path: String,
+    pub log_dir: String,
+}
+
+/// When the current backend process was spawned
+#[derive(Debug, Clone, Copy)]
+struct SpawnRecord {
+    pid: u32,
+    at: Instant,
+    /// Milliseconds since the Unix epoch
+    timestamp: u64,
+}
+
+/// Where a backend process reported it is listening
+#[derive(Debug, Clone)]
+struct BoundAddress {
+    pid: u32,
+    host: String,
+    port: u16,
+}
+
+/// Contents of the PID file, used to find a backend left behind by a crashed app
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PidRecord {
+    pid: u32,
+    /// Milliseconds since the Unix epoch
+    spawned_at: u64,
+    /// Compared on the next launch so a reused PID is never killed
+    command_line: String,
+}
+
+/// Payload of the `backend://starting|ready|stopped|failed` events
+#[derive(Debug, Clone, Serialize)]
+struct LifecyclePayload {
+    pid: Option<u32>,
+    /// Milliseconds since the Unix epoch
+    timestamp: u64,
+    reason: Option<String>,
+}
+
+/// Payload of the `backend://startup-failed` event
+#[derive(Debug, Clone, Serialize)]
+struct StartupFailedPayload {
+    pid: u32,
+    reason: String,
+    /// backend.err.log, for the "open log" link in the failure dialog
+    log_path: String,
+    /// Last lines of that log, so the dialog can show what went wrong
+    log_tail: Vec<String>,
+}
+
+/// Payload of the `backend://unhealthy` event
+#[derive(Debug, Clone, Serialize)]
+struct UnhealthyPayload {
+    pid: u32,
+    failures: u32,
+    last_error: String,
+    /// Whether the manager is about to restart the backend
+    restarting: bool,
+}
+
+/// Payload of the `backend://wedged` event, also saved as the diagnostic snapshot
+#[derive(Debug, Clone, Serialize)]
+struct WedgedPayload {
+    pid: u32,
+    /// Seconds since the last stdout/stderr line
+    silent_secs: u64,
+    uptime_secs: Option<u64>,
+    last_error: String,
+    /// Last lines of the backend stderr log
+    log_tail: Vec<String>,
+    /// When the snapshot was taken, in milliseconds since the Unix epoch
+    timestamp: u64,
+}
+
+/// Payload of the `backend://memory-warning` and `backend://memory-limit` events
+#[derive(Debug, Clone, Serialize)]
+struct MemoryPayload {
+    pid: u32,
+    /// Resident memory of the backend and all of its descendants
+    used_bytes: u64,
+    /// The ceiling that was crossed
+    threshold_bytes: u64,
+}
+
+/// Written to the log directory when the memory limit forces a restart
+#[derive(Debug, Clone, Serialize)]
+struct MemoryIncident {
+    pid: u32,
+    used_bytes: u64,
+    limit_bytes: u64,
+    uptime_secs: Option<u64>,
+    /// Milliseconds since the Unix epoch
+    timestamp: u64,
+    log_tail: Vec<String>,
+}
+
+/// Payload of the `backend://resumed` event
+#[derive(Debug, Clone, Serialize)]
+struct ResumedPayload {
+    slept_secs: u64,
+    /// The backend didn't survive the sleep and is being restarted
+    restarting: bool,
+}
+
+/// Payload of the `backend://deps-progress` event
+#[derive(Debug, Clone, Serialize)]
+struct DepsProgressPayload {
+    stream: LogStream,
+    line: String,
+}
+
+/// Payload of the `backend://url-changed` event
+#[derive(Debug, Clone, Serialize)]
+struct UrlChangedPayload {
+    pid: u32,
+    url: String,
+}
+
+/// Payload of the `backend://dependency-failed` event
+#[derive(Debug, Clone, Serialize)]
+struct DependencyFailedPayload {
+    name: String,
+    depends_on: String,
+    reason: String,
+}
+
+/// Where the output of one process goes
+struct LogFiles {
+    stdout: PathBuf,
+    stderr: PathBuf,
+    json: PathBuf,
+}
+
+/// What `kill_descendants` did to the tree under one PID
+#[derive(Debug, Default)]
+struct KillReport {
+    /// PIDs a signal or taskkill was delivered to
+    signaled: Vec<u32>,
+    /// PIDs still running after the last forced kill
+    survived: Vec<u32>,
+}
+
+/// Payload of the `backend://exited` event, sent for every backend process that ends
+#[derive(Debug, Clone, Serialize)]
+struct ExitedPayload {
+    pid: u32,
+    code: Option<i32>,
+    signal: Option<i32>,
+    /// Exit code 0, as opposed to a crash or a kill
+    clean: bool,
+    /// The manager stopped or replaced the process itself
+    expected: bool,
+}
+
+/// Payload of the `backend://crashed` event
+#[derive(Debug, Clone, Serialize)]
+struct CrashedPayload {
+    pid: u32,
+    code: Option<i32>,
+    signal: Option<i32>,
+    attempts: u32,
+}
 
 /// Backend process manager
-pub struct BackendManager {
-    processes: Mutex<Vec<CommandChild>>,
+pub struct BackendManager<R: Runtime = Wry> {
+    state: Mutex<BackendState>,
+    last_error: Mutex<Option<String>>,
+    /// How the most recent backend process ended
+    last_exit: Mutex<Option<TerminatedPayload>>,
+    /// Changeable at runtime through `set_restart_policy`
+    restart_policy: Mutex<RestartPolicy>,
+    restart_backoff: RestartBackoff,
+    shutting_down: AtomicBool,
+    /// Set for good by `cancel_start` once the app is exiting; nothing is spawned after
+    exiting: AtomicBool,
+    /// The uv sync in progress, so `cancel_start` can kill it
+    sync_child: Mutex<Option<CommandChild>>,
+    shutdown_timeouts: ShutdownTimeouts,
+    /// Port of the backend the frontend talks to; moves on `reload_backend`
+    port: AtomicU16,
+    /// Port asked for in the environment, claimed again on every start
+    preferred_port: u16,
+    /// Which pair of log files the serving backend writes to; a reload
+    /// candidate uses the other pair so the overlap doesn't interleave
+    log_slot: AtomicUsize,
+    health_check: HealthCheck,
+    watchdog: HealthWatchdog,
+    memory_watchdog: MemoryWatchdog,
+    http: reqwest::Client,
+    /// Endpoint that makes the backend exit on its own
+    shutdown_path: String,
+    /// Endpoint that makes the backend stop trading before it is stopped
+    quiesce_path: String,
+    /// Python module of the API server, passed to `uv run -m`
+    main_module: String,
+    /// Time a spawned backend gets to become ready before it is killed
+    startup_timeout: Duration,
+    /// Spawn attempts `start_with_args` makes before giving up
+    spawn_attempts: u32,
+    spawn_retry_delay: Duration,
+    /// Run `uv sync` before every start; off for users who manage the venv themselves
+    auto_sync: bool,
+    /// Never let uv sync reach the network; see `OFFLINE_ENV`
+    offline: bool,
+    uv: UvSidecar,
+    /// The uv in use once `uv()` found one
+    uv_resolved: Mutex<Option<UvProgram>>,
+    /// Records which uv.lock the venv matches, to skip offline syncs
+    sync_marker: PathBuf,
+    /// Also write backend output as JSON lines for log aggregation
+    json_logs: bool,
+    /// Mirror every backend line into the app log, for watching it live during development
+    debug: bool,
+    /// Log backend lines recognized as warnings or errors in the app log at that level
+    log_levels: bool,
+    /// Appended to the backend command line; kept for restarts
+    extra_args: Mutex<Vec<String>>,
+    /// Variables set on the backend process. Values may be secrets and are never logged.
+    env: HashMap<String, String>,
+    /// Size at which a backend log file is rotated
+    max_log_bytes: u64,
+    /// Rotated `<log>.N` files to keep per log
+    max_log_files: usize,
+    /// Prefix log lines with a UTC timestamp; off when the backend stamps its own output
+    timestamps: bool,
+    /// Startup line that signals the server is accepting connections
+    ready_banner: Regex,
+    last_spawn: Mutex<Option<SpawnRecord>>,
+    start_count: AtomicU32,
+    /// When each tracked process last wrote a line to stdout or stderr
+    last_output: Mutex<HashMap<u32, Instant>>,
+    /// Address parsed from the startup banner of the current process
+    bound_address: Mutex<Option<BoundAddress>>,
+    /// How a starting backend is recognized as ready
+    readiness: Readiness,
+    /// Process that last printed the `Readiness::LogMarker` marker
+    marker_seen: Mutex<Option<u32>>,
+    /// Where the backend runs and uv sync creates `.venv`: a copy of the bundle
+    /// in the app data dir, or the `TRADEBYWEEX_BACKEND_DIR` override
     backend_path: PathBuf,
+    /// The read-only bundle `backend_path` is copied from; never written to
+    bundled_backend: Option<PathBuf>,
+    /// Held while the copy is brought up to date
+    copy_lock: Mutex<()>,
     log_dir: PathBuf,
-    app: AppHandle,
+    /// Why `log_dir` is unusable; backend output is then only mirrored to the app log
+    log_dir_error: Option<String>,
+    /// Records the running backend until it is stopped cleanly
+    pid_file: PathBuf,
+    maintenance: Mutex<MaintenanceSchedule>,
+    /// Where `maintenance` is persisted across app restarts
+    maintenance_file: PathBuf,
+    launch_settings: Mutex<LaunchSettings>,
+    /// Where `launch_settings` is persisted across app restarts
+    settings_file: PathBuf,
+    /// Stop the backend after this long without `activity_heartbeat`; `None` disables it
+    idle_timeout: Option<Duration>,
+    last_activity: Mutex<Instant>,
+    /// The backend was stopped for being idle and starts again on activity
+    idle_stopped: AtomicBool,
+    /// Owns the processes and serializes everything done to them, set up by
+    /// `spawn_supervisor`
+    supervisor: OnceLock<Supervisor>,
+    /// Every spawned process is assigned to it, so the OS kills the backend tree
+    /// when the app dies without running its shutdown
+    #[cfg(windows)]
+    job: Option<crate::job_object::JobObject>,
+    app: AppHandle<R>,
 }
 
 const MAIN_MODULE: &str = "valuecell.server.main";
+/// Module to run instead of `MAIN_MODULE`, for forks with another entry point
+const MAIN_MODULE_ENV: &str = "VALUECELL_MAIN_MODULE";
+/// Extra modules `start_process` may launch next to the API server
+const ALLOWED_MODULES: &[&str] = &["valuecell.server.worker"];
+/// Registry name of the second instance while `reload_backend` brings it up
+const RELOAD_PROCESS_NAME: &str = "backend-reload";
+/// Run a checked-out backend instead of the bundled one, e.g. during development
+const BACKEND_DIR_ENV: &str = "TRADEBYWEEX_BACKEND_DIR";
+/// Earlier name of `BACKEND_DIR_ENV`, still honoured when that isn't set
+const LEGACY_BACKEND_DIR_ENV: &str = "VALUECELL_BACKEND_PATH";
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(3);
+/// Together with the other steps this stays within `DEFAULT_STOP_DEADLINE`
+const DEFAULT_QUIESCE_TIMEOUT: Duration = Duration::from_secs(2);
+/// Milliseconds to wait for the backend to acknowledge the quiesce request
+const QUIESCE_TIMEOUT_ENV: &str = "VALUECELL_QUIESCE_TIMEOUT_MS";
+/// Milliseconds to wait for the backend after the HTTP shutdown request
+const GRACEFUL_TIMEOUT_ENV: &str = "VALUECELL_GRACEFUL_TIMEOUT_MS";
+/// Milliseconds to wait for descendants after SIGINT before sending SIGKILL
+const KILL_TIMEOUT_ENV: &str = "VALUECELL_KILL_TIMEOUT_MS";
+/// How long force-killed descendants get to disappear before we give up on them
+const FORCE_KILL_VERIFY_TIMEOUT: Duration = Duration::from_secs(1);
+const DEFAULT_STOP_DEADLINE: Duration = Duration::from_secs(10);
+/// Milliseconds a whole stop may take before remaining processes are killed outright
+const STOP_DEADLINE_ENV: &str = "VALUECELL_STOP_DEADLINE_MS";
+/// Headroom past the deadline for the final kills when bounding a blocking stop
+const STOP_BUDGET_SLACK: Duration = Duration::from_secs(2);
+const DEFAULT_PORT: u16 = 8000;
+const PORT_ENV: &str = "VALUECELL_PORT";
+/// Printed by uvicorn once it accepts connections; needs a `port` and optionally a `host` group
+const DEFAULT_READY_BANNER: &str = r"Uvicorn running on https?://(?P<host>[^:\s]+):(?P<port>\d+)";
+const READY_BANNER_ENV: &str = "VALUECELL_READY_BANNER";
+/// `http`, `log` or `log:<marker>`; see `Readiness`
+const READINESS_ENV: &str = "VALUECELL_READINESS";
+/// Waited for by `log` readiness without an explicit marker
+const DEFAULT_READY_MARKER: &str = "Uvicorn running on";
+const API_PREFIX: &str = "/api/v1";
+const HEALTH_PATH: &str = "/api/v1/healthz";
+const HEALTH_PATH_ENV: &str = "VALUECELL_HEALTH_PATH";
+const SHUTDOWN_PATH: &str = "/api/v1/admin/shutdown";
+const SHUTDOWN_PATH_ENV: &str = "VALUECELL_SHUTDOWN_PATH";
+const QUIESCE_PATH: &str = "/api/v1/admin/quiesce";
+const QUIESCE_PATH_ENV: &str = "VALUECELL_QUIESCE_PATH";
+/// Kept short so a hung server barely delays quitting
+const SHUTDOWN_REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+const WATCHDOG_RESTART_ENV: &str = "VALUECELL_WATCHDOG_RESTART";
+/// Seconds without backend output before a failing health probe means wedged; unset or 0 disables
+const SILENCE_TIMEOUT_ENV: &str = "VALUECELL_SILENCE_TIMEOUT_SECS";
+/// Liveness check interval unless `VALUECELL_HEARTBEAT_SECS` says otherwise
+const DEFAULT_HEARTBEAT_SECS: u64 = 60;
+/// Seconds between liveness checks of every tracked process; `0` disables them
+const HEARTBEAT_ENV: &str = "VALUECELL_HEARTBEAT_SECS";
+/// Time the exit event of a vanished process gets to arrive before the heartbeat handles it
+const HEARTBEAT_EXIT_GRACE: Duration = Duration::from_secs(2);
+/// Diagnostic snapshot of the last wedged backend
+const WEDGED_SNAPSHOT_FILE: &str = "wedged.json";
+const WEDGED_SNAPSHOT_LINES: usize = 100;
+/// Megabytes of backend memory (including descendants) that trigger a warning
+const MEMORY_WARN_ENV: &str = "VALUECELL_MEMORY_WARN_MB";
+/// Megabytes of backend memory (including descendants) that trigger a restart
+const MEMORY_LIMIT_ENV: &str = "VALUECELL_MEMORY_LIMIT_MB";
+const BANNER_POLL_INTERVAL: Duration = Duration::from_millis(200);
+const DEFAULT_STARTUP_TIMEOUT_SECS: u64 = 60;
+const STARTUP_TIMEOUT_ENV: &str = "VALUECELL_STARTUP_TIMEOUT";
+/// Log lines attached to `backend://startup-failed`
+const STARTUP_FAILED_TAIL_LINES: usize = 50;
+const STDOUT_LOG_FILE: &str = "backend.out.log";
+const STDERR_LOG_FILE: &str = "backend.err.log";
+/// Both streams as `{"ts", "stream", "line"}` objects, written when `VALUECELL_JSON_LOGS` is set
+const JSON_LOG_FILE: &str = "backend.jsonl";
+const JSON_LOGS_ENV: &str = "VALUECELL_JSON_LOGS";
+const ENV_ALLOWLIST: &[&str] = &[
+    "APP_ENVIRONMENT",
+    "API_DEBUG",
+    "AGENT_DEBUG_MODE",
+    "AUTO_DETECT_PROVIDER",
+    "AUTO_TRADING_EXCHANGE",
+    "CORS_ORIGINS",
+    "FALLBACK_PROVIDERS",
+    "PRIMARY_PROVIDER",
+    "SEC_EMAIL",
+    "TIMEZONE",
+    "WEB_SEARCH_PROVIDER",
+    "XUEQIU_TOKEN",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+];
+const ENV_ALLOWED_PREFIXES: &[&str] = &["VALUECELL_", "OKX_", "WEEX_"];
+const ENV_ALLOWED_SUFFIXES: &[&str] = &["_API_KEY", "_BASE_URL", "_WEBHOOK_URL"];
+const SHELL_METACHARACTERS: &[char] = &[';', '|', '&', '$', '`', '<', '>', '\n', '\r'];
+const PID_FILE: &str = "backend.pid";
+const STATS_FILE: &str = "stats.json";
+const MAINTENANCE_FILE: &str = "maintenance.json";
+const SETTINGS_FILE: &str = "settings.json";
+/// How often the maintenance scheduler looks at the clock
+const MAINTENANCE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Minutes without UI activity before an idle backend is stopped; unset or 0 disables it
+const IDLE_SHUTDOWN_ENV: &str = "VALUECELL_IDLE_SHUTDOWN_MINS";
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(60);
+/// How often the resume watcher compares the wall clock
+const RESUME_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Clock jump beyond the poll interval that counts as the machine having slept
+const RESUME_MIN_GAP: Duration = Duration::from_secs(30);
+/// Time a backend gets to recover after a wake before it is restarted
+const RESUME_GRACE_PERIOD: Duration = Duration::from_secs(10);
+/// A restart overdue by more than this (the machine slept through it) waits a day
+const MAINTENANCE_LATE_LIMIT: chrono::TimeDelta = chrono::TimeDelta::minutes(30);
+/// Output of the last dependency installs
+const UV_SYNC_LOG_FILE: &str = "uv-sync.log";
+/// Deepest path uv sync creates below the backend directory, e.g. package data in
+/// `.venv\Lib\site-packages`, counted against the Windows path limit
+const BACKEND_PATH_HEADROOM: usize = 150;
+/// Longest log file name below the log directory, including rotation suffixes
+const LOG_PATH_HEADROOM: usize = 64;
+/// How often a backend log file that failed a write is reopened; lines in
+/// between are dropped, while the output is still drained
+const LOG_REOPEN_INTERVAL: Duration = Duration::from_secs(30);
+/// uv-sync.log lines attached to `BackendError::DepsSyncFailed`
+const DEPS_SYNC_FAILED_TAIL_LINES: usize = 20;
+const DEFAULT_MAX_LOG_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_MAX_LOG_FILES: usize = 5;
+const DEFAULT_LOG_MAX_AGE_DAYS: u64 = 7;
+/// Days after which backend logs are deleted at launch
+const LOG_MAX_AGE_ENV: &str = "VALUECELL_LOG_MAX_AGE_DAYS";
+/// `never`, `always`, `on-failure` or `on-failure:<max retries>`
+const RESTART_POLICY_ENV: &str = "VALUECELL_RESTART_POLICY";
+/// Set to `1` to start the backend without running `uv sync` first
+const SKIP_SYNC_ENV: &str = "VALUECELL_SKIP_SYNC";
+/// Set to `1` on machines without internet access. uv sync then runs as
+/// `uv sync --frozen --offline`, installing from uv.lock with cached packages
+/// only, and is skipped altogether while the venv still matches uv.lock.
+const OFFLINE_ENV: &str = "VALUECELL_OFFLINE";
+/// Size and modification time of the uv.lock the venv was last synced with
+const SYNC_MARKER_FILE: &str = "deps-synced";
+const LOG_TIMESTAMPS_ENV: &str = "VALUECELL_LOG_TIMESTAMPS";
+/// Set to `1` to mirror backend output into the app log
+const DEBUG_ENV: &str = "VALUECELL_DEBUG";
+/// Set to `1` to log backend warnings and errors in the app log at their level,
+/// e.g. `ERROR:root:...`; the log files get every line regardless
+const LOG_LEVELS_ENV: &str = "VALUECELL_LOG_LEVELS";
+const RESTART_SETTLE_DELAY: Duration = Duration::from_millis(500);
+const DEFAULT_SPAWN_ATTEMPTS: u32 = 3;
+const SPAWN_ATTEMPTS_ENV: &str = "VALUECELL_SPAWN_ATTEMPTS";
+const DEFAULT_SPAWN_RETRY_DELAY_MS: u64 = 1000;
+const SPAWN_RETRY_DELAY_ENV: &str = "VALUECELL_SPAWN_RETRY_DELAY_MS";
+/// A backend that stayed up this long is considered healthy again
+const RESTART_RESET_AFTER: Duration = Duration::from_secs(60);
+
+/// Lock `mutex` even when a panic while it was held poisoned it. The manager's
+/// mutexes only guard plain values that are never left half-updated, and one
+/// panicking request must not make every later one panic too, least of all the
+/// shutdown that kills the backend on exit.
+fn lock_or_recover<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Parse `name` from the environment, falling back to `default` when unset or invalid
+fn env_or<T: FromStr + Display>(name: &str, default: T) -> T {
+    match std::env::var(name) {
+        Ok(value) => value.trim().parse().unwrap_or_else(|_| {
+            log::warn!("Ignoring invalid {}={:?}, using {}", name, value, default);
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
+/// Whether `name` is set to `1`, `true` or `yes`
+fn env_flag(name: &str) -> bool {
+    std::env::var(name).is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"))
+}
 
-impl BackendManager {
-    fn wait_until_terminated(mut rx: Receiver<CommandEvent>) {
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
+
+impl<R: Runtime> BackendManager<R> {
+    /// Drain `rx` until the process ends, passing each output line to `on_line`
+    fn wait_until_terminated(
+        mut rx: Receiver<CommandEvent>,
+        mut on_line: impl FnMut(LogStream, &str),
+    ) -> Option<TerminatedPayload> {
         while let Some(event) = rx.blocking_recv() {
-            if matches!(event, CommandEvent::Terminated(_)) {
-                break;
+            let (stream, line) = match event {
+                CommandEvent::Stdout(line) => (LogStream::Stdout, line),
+                CommandEvent::Stderr(line) => (LogStream::Stderr, line),
+                CommandEvent::Terminated(payload) => return Some(payload),
+                _ => continue,
+            };
+            let text = String::from_utf8_lossy(&line);
+            // Progress bars redraw with carriage returns; keep only the latest frame
+            let text = text.trim_end_matches(['\n', '\r']);
+            let text = text.rsplit('\r').next().unwrap_or_default();
+            on_line(stream, text);
+        }
+        None
+    }
+
+    /// Fails only when descendants are still alive after the forced kill
+    async fn kill_descendants_best_effort(&self, parent_pid: u32) -> Result<()> {
+        let report = self.kill_descendants(parent_pid).await;
+        if report.survived.is_empty() {
+            if !report.signaled.is_empty() {
+                log::info!(
+                    "Descendants {:?} of {} are gone",
+                    report.signaled,
+                    parent_pid
+                );
             }
+            return Ok(());
         }
+        log::error!(
+            "Descendants {:?} of {} are still running and may be orphaned (signaled: {:?})",
+            report.survived,
+            parent_pid,
+            report.signaled
+        );
+        Err(anyhow!(
+            "descendants {:?} of {} survived a forced kill and may be orphaned",
+            report.survived,
+            parent_pid
+        ))
     }
 
-    fn kill_descendants_best_effort(&self, parent_pid: u32) {
-        // Try to kill all descendants of the given PID (macOS/Linux)
-        // This is best-effort and ignores errors on platforms without `pkill`.
-        // First, send SIGINT (Ctrl+C equivalent) and wait up to 5 seconds.
-        // If processes are still running, escalate to SIGKILL.
-        let pid_str = parent_pid.to_string();
+    /// Ask the descendants of `parent_pid` to stop, wait up to `before_kill`,
+    /// then kill what is left, twice if needed
+    async fn kill_descendants(&self, parent_pid: u32) -> KillReport {
+        let mut report = KillReport::default();
+        if !process_tree::descendants_alive(parent_pid) {
+            log::info!("No descendants of {} left to kill", parent_pid);
+            return report;
+        }
 
-        // Send SIGINT (Ctrl+C equivalent)
-        if let Ok((_rx, _child)) = self
-            .app
-            .shell()
-            .command("pkill")
-            .args(["-INT", "-P", &pid_str])
-            .spawn()
+        let before_kill = self.shutdown_timeouts.before_kill;
+        // Taken up front at every depth: once a parent dies, its children are
+        // reparented (unix) or out of reach of `/T` (windows)
+        let tree = process_tree::descendants(parent_pid);
+
+        #[cfg(unix)]
         {
-            log::info!(
-                "Issued SIGINT (Ctrl+C) pkill for descendants of {}",
-                parent_pid
+            log::info!("Killing descendants {:?} of {}", tree, parent_pid);
+            // Send SIGINT (Ctrl+C equivalent)
+            report
+                .signaled
+                .extend(self.signal_pids(libc::SIGINT, &tree, "SIGINT"));
+
+            if process_tree::wait_for_tree_exit(parent_pid, &tree, before_kill).await {
+                log::info!("Descendants of {} exited, skipping SIGKILL", parent_pid);
+                return report;
+            }
+
+            // Escalate to SIGKILL if processes are still running
+            log::warn!(
+                "Descendants of {} still running {:?} after SIGINT, sending SIGKILL",
+                parent_pid,
+                before_kill
             );
+            report.signaled.extend(self.force_kill_pids(&tree));
         }
 
-        // Wait up to 3 seconds for graceful termination
-        std::thread::sleep(Duration::from_secs(3));
-
-        // Escalate to SIGKILL if processes are still running
-        if let Ok((_rx, _child)) = self
-            .app
-            .shell()
-            .command("pkill")
-            .args(["-KILL", "-P", &pid_str])
-            .spawn()
+        #[cfg(windows)]
         {
             log::info!(
-                "Issued SIGKILL (forceful) pkill for descendants of {}",
+                "Killing process tree of {} via taskkill (windows)",
                 parent_pid
             );
+            let pid_str = parent_pid.to_string();
+            // Ask the whole tree to close
+            self.run_kill_utility("taskkill", &["/PID", &pid_str, "/T"], "graceful taskkill");
+            report.signaled.extend(&tree);
+
+            if process_tree::wait_for_descendants_exit(parent_pid, before_kill).await {
+                log::info!(
+                    "Process tree of {} exited, skipping forced kill",
+                    parent_pid
+                );
+                return report;
+            }
+
+            // Escalate to a forced tree kill if processes are still running
+            log::warn!(
+                "Process tree of {} still running {:?} after taskkill, forcing",
+                parent_pid,
+                before_kill
+            );
+            self.run_kill_utility(
+                "taskkill",
+                &["/PID", &pid_str, "/T", "/F"],
+                "forceful taskkill",
+            );
+            self.force_kill_pids(&tree);
+        }
+
+        if process_tree::wait_for_tree_exit(parent_pid, &tree, FORCE_KILL_VERIFY_TIMEOUT).await {
+            self.reap_descendants(&tree);
+            return report;
+        }
+        let leaked = process_tree::surviving(parent_pid, &tree);
+        log::warn!(
+            "Descendants {:?} of {} survived the forced kill, killing them once more",
+            leaked,
+            parent_pid
+        );
+        report.signaled.extend(self.force_kill_pids(&leaked));
+        report.signaled.sort_unstable();
+        report.signaled.dedup();
+        if !process_tree::wait_for_tree_exit(parent_pid, &leaked, FORCE_KILL_VERIFY_TIMEOUT).await {
+            report.survived = process_tree::surviving(parent_pid, &leaked);
         }
+        self.reap_descendants(&tree);
+        report
     }
 
-    fn spawn_backend_process(&self) -> Result<(Receiver<CommandEvent>, CommandChild)> {
-        log::info!("Command: uv run -m {}", MAIN_MODULE);
+    /// Wait on those killed descendants that ended up as our own children. They
+    /// normally belong to uv or init, and the plugin waits on the child it spawned.
+    fn reap_descendants(&self, pids: &[u32]) {
+        #[cfg(unix)]
+        {
+            let reaped = process_tree::reap(pids);
+            if !reaped.is_empty() {
+                log::info!("Reaped exited descendants {:?}", reaped);
+            }
+        }
+        #[cfg(windows)]
+        let _ = pids;
+    }
 
-        let sidecar_command = self
-            .app
-            .shell()
-            .sidecar("uv")
-            .context("Failed to create uv sidecar command")?
-            .args(["run", "-m", MAIN_MODULE])
-            .current_dir(&self.backend_path);
+    /// SIGKILL on unix, `taskkill /F` on Windows, for each of `pids`; returns
+    /// those it was delivered to
+    fn force_kill_pids(&self, pids: &[u32]) -> Vec<u32> {
+        if pids.is_empty() {
+            return Vec::new();
+        }
 
-        sidecar_command
-            .spawn()
-            .context("Failed to spawn backend process")
+        #[cfg(unix)]
+        return self.signal_pids(libc::SIGKILL, pids, "SIGKILL");
+
+        #[cfg(windows)]
+        {
+            let pid_strs: Vec<String> = pids.iter().map(u32::to_string).collect();
+            let mut args = vec!["/F"];
+            for pid in &pid_strs {
+                args.extend(["/PID", pid.as_str()]);
+            }
+            self.run_kill_utility("taskkill", &args, "forceful taskkill");
+            pids.to_vec()
+        }
     }
 
-    pub fn new(app: AppHandle) -> Result<Self> {
-        let resource_root = app
-            .path()
-            .resolve(".", BaseDirectory::Resource)
-            .context("Failed to resolve resource root")?;
+    /// Deliver `signal` to each of `pids` directly; returns those it reached.
+    /// Processes already gone are skipped, any other failure is logged.
+    #[cfg(unix)]
+    fn signal_pids(&self, signal: libc::c_int, pids: &[u32], name: &str) -> Vec<u32> {
+        let mut signaled = Vec::with_capacity(pids.len());
+        for &pid in pids {
+            match process_tree::send_signal(pid, signal) {
+                Ok(true) => signaled.push(pid),
+                Ok(false) => log::info!("Process {} was gone before {}", pid, name),
+                Err(e) => log::error!("Failed to send {} to process {}: {}", name, pid, e),
+            }
+        }
+        if !signaled.is_empty() {
+            log::info!("Sent {} to {:?}", name, signaled);
+        }
+        signaled
+    }
 
-        let backend_path = resource_root.join("backend");
-        if !backend_path.exists() {
-            return Err(anyhow!("Backend directory not found at {:?}", backend_path));
+    #[cfg(windows)]
+    fn run_kill_utility(&self, program: &str, args: &[&str], description: &str) {
+        match self.app.shell().command(program).args(args).spawn() {
+            Ok((_rx, _child)) => log::info!("Issued {} ({:?})", description, args),
+            Err(e) => log::warn!("Could not run {} for {}: {}", program, description, e),
         }
+    }
 
-        let log_dir = app
-            .path()
-            .app_log_dir()
-            .context("Failed to get log directory")?
-            .join("backend");
+    /// Run `uv run -m <module> <args>` in the backend directory. Only the API
+    /// server gets a `port`.
+    fn spawn_module(
+        &self,
+        module: &str,
+        args: &[String],
+        port: Option<u16>,
+    ) -> Result<(Receiver<CommandEvent>, CommandChild), BackendError> {
+        self.check_backend_dir()?;
+        log::info!(
+            "Command: uv run -m {} {}{}",
+            module,
+            args.join(" "),
+            port.map_or_else(String::new, |port| format!(" (port {})", port))
+        );
 
-        create_dir_all(&log_dir).context("Failed to create log directory")?;
+        let mut sidecar_command = self
+            .uv_command()?
+            .args(["run", "-m", module])
+            .args(args)
+            .envs(&self.env)
+            .current_dir(&self.backend_path);
+        if let Some(port) = port {
+            sidecar_command = sidecar_command.env("API_PORT", port.to_string());
+        }
 
-        log::info!("Backend path: {:?}", backend_path);
-        log::info!("Log directory: {:?}", log_dir);
+        sidecar_command
+            .spawn()
+            .map_err(|e| Self::spawn_failed_in("backend", &self.backend_path, e))
+            .inspect_err(|_| self.forget_uv())
+    }
 
-        Ok(Self {
-            processes: Mutex::new(Vec::new()),
-            backend_path,
-            log_dir,
-            app,
-        })
+    /// Spawn `module`, stream its output into `logs` and register it as `name`
+    fn launch(
+        &self,
+        processes: &mut ProcessRegistry,
+        name: &str,
+        module: &str,
+        args: &[String],
+        port: Option<u16>,
+        logs: LogFiles,
+    ) -> Result<u32, BackendError> {
+        // Checked before spawning, so a duplicate is never started
+        if let Some(pid) = processes.pid_of(name) {
+            return Err(BackendError::AlreadyRunning { pid: Some(pid) });
+        }
+        let (rx, child) = self.spawn_module(module, args, port)?;
+        let pid = child.pid();
+        #[cfg(windows)]
+        if let Some(job) = &self.job {
+            // Children uv starts from here on join too; any started before stay outside
+            if let Err(e) = job.assign(pid) {
+                log::warn!("Failed to add process {} to the job object: {}", pid, e);
+            }
+        }
+        let (exit_tx, exited) = watch::channel(false);
+        self.stream_backend_logs(rx, pid, logs, exit_tx);
+        self.spawn_heartbeat(pid);
+        let registered = processes.insert(ProcessHandle {
+            name: name.to_string(),
+            module: module.to_string(),
+            args: args.to_vec(),
+            pid,
+            started_at: unix_millis(),
+            started: Instant::now(),
+            state: BackendState::Starting,
+            depends_on: None,
+            port,
+            child,
+            exited,
+        });
+        if let Err(rejected) = registered {
+            // Untracked, so its exit isn't taken for a crash
+            if let Err(e) = rejected.child.kill() {
+                log::error!("Failed to kill duplicate process {}: {}", pid, e);
+            }
+            return Err(BackendError::AlreadyRunning {
+                pid: processes.pid_of(name),
+            });
+        }
+        Ok(pid)
     }
 
-    fn install_dependencies(&self) -> Result<()> {
-        let sidecar_command = self
-            .app
-            .shell()
-            .sidecar("uv")
-            .context("Failed to create uv sidecar command")?
-            .args(["sync", "--frozen"])
-            .current_dir(&self.backend_path);
+    /// Launch an additional backend module, e.g. a data-ingestion worker, under
+    /// `name`. It is stopped together with the API server but never restarted.
+    ///
+    /// With `depends_on`, that process has to be ready; the supervisor waits for
+    /// it with `wait_for_dependency` before queueing the launch. Dependents are
+    /// stopped before their dependency.
+    pub fn start_process(
+        &self,
+        processes: &mut ProcessRegistry,
+        name: &str,
+        module: &str,
+        args: &[String],
+        depends_on: Option<&str>,
+    ) -> Result<u32, BackendError> {
+        Self::validate_process(name, module, args)?;
+        self.uv()?;
+        if let Some(pid) = processes.pid_of(name) {
+            return Err(BackendError::AlreadyRunning { pid: Some(pid) });
+        }
+        if let Some(dependency) = depends_on {
+            // It may have died after the supervisor saw it ready
+            if processes.state_of(dependency) != Some(BackendState::Running) {
+                let reason = "it is not running".to_string();
+                return Err(self.dependency_failed(name, dependency, reason));
+            }
+        }
+        self.refresh_backend_copy()?;
 
-        let (rx, _child) = sidecar_command.spawn().context("Failed to spawn uv sync")?;
-        Self::wait_until_terminated(rx);
+        let logs = self.process_log_files(name);
+        let pid = self.launch(processes, name, module, args, None, logs)?;
+        processes.set_depends_on(pid, depends_on);
+        // Workers have no readiness probe; spawned is as far as we can tell
+        processes.set_state(pid, BackendState::Running);
+        Ok(pid)
+    }
 
-        log::info!("✓ Dependencies installed/verified");
-        Ok(())
+    /// Allowlisted module, a name that can't pass for the API server, and
+    /// arguments without shell metacharacters
+    fn validate_process(name: &str, module: &str, args: &[String]) -> Result<(), BackendError> {
+        if !ALLOWED_MODULES.contains(&module) {
+            return Err(BackendError::ModuleNotAllowed(module.to_string()));
+        }
+        let valid_name = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            && !name.starts_with(BACKEND_PROCESS_NAME);
+        if !valid_name {
+            return Err(BackendError::InvalidProcessName(name.to_string()));
+        }
+        Self::check_arguments(args)
     }
 
-    pub fn start_all(&self) -> Result<()> {
-        self.install_dependencies()?;
+    /// The service configured as `name` in the launch settings
+    pub fn service(&self, name: &str) -> Result<ServiceConfig, BackendError> {
+        self.launch_settings()
+            .services
+            .into_iter()
+            .find(|service| service.name == name)
+            .ok_or_else(|| BackendError::UnknownProcess(name.to_string()))
+    }
 
-        let mut processes = self.processes.lock().unwrap();
+    /// Start the service configured as `name` right away; `"backend"` starts the
+    /// API server. Its dependency already has to be ready.
+    pub fn start_service(
+        &self,
+        processes: &mut ProcessRegistry,
+        name: &str,
+    ) -> Result<u32, BackendError> {
+        if name == BACKEND_PROCESS_NAME {
+            return self.start_backend(processes);
+        }
+        let service = self.service(name)?;
+        self.start_process(
+            processes,
+            &service.name,
+            &service.module,
+            &service.args,
+            service.depends_on.as_deref(),
+        )
+    }
 
-        match self.spawn_backend_process() {
-            Ok((rx, child)) => {
-                self.stream_backend_logs(rx);
-                log::info!("Process {} added to process list", child.pid());
-                processes.push(child);
-            }
-            Err(e) => log::error!("Failed to start backend server: {}", e),
+    /// Queue a start of every configured service that isn't running, in order.
+    /// Each waits for its dependency outside the queue, so the caller isn't held up.
+    fn start_services(&self) {
+        let services = self.launch_settings().services;
+        let Ok(supervisor) = self.supervisor().cloned() else {
+            return;
+        };
+        if services.is_empty() {
+            return;
         }
+        tauri::async_runtime::spawn(async move {
+            for service in services {
+                if supervisor.processes().pid_of(&service.name).is_some() {
+                    continue;
+                }
+                if let Err(e) = supervisor.start_service(service.name.clone()).await {
+                    log::error!("Failed to start service {}: {}", service.name, e.report());
+                }
+            }
+        });
+    }
 
-        Ok(())
+    /// Wait until `dependency` is registered and ready, for at most `timeout` (the
+    /// backend startup timeout by default). A dependency that fails or stays
+    /// unready that long is reported as a `backend://dependency-failed` event.
+    pub async fn wait_for_dependency(
+        &self,
+        name: &str,
+        dependency: &str,
+        timeout: Option<Duration>,
+    ) -> Result<(), BackendError> {
+        let timeout = timeout.unwrap_or(self.startup_timeout);
+        let deadline = Instant::now() + timeout;
+        let reason = loop {
+            let state = self.tracked().state_of(dependency);
+            match state {
+                Some(BackendState::Running) => return Ok(()),
+                Some(BackendState::Failed) => break "it failed to start".to_string(),
+                _ if self.shutting_down.load(Ordering::SeqCst) => {
+                    break "the app is shutting down".to_string()
+                }
+                _ if Instant::now() >= deadline => {
+                    break match state {
+                        Some(_) => format!("not ready after {:?}", timeout),
+                        None => format!("not running after {:?}", timeout),
+                    }
+                }
+                _ => tokio::time::sleep(BANNER_POLL_INTERVAL).await,
+            }
+        };
+        Err(self.dependency_failed(name, dependency, reason))
     }
 
-    /// Stop all backend processes
-    pub fn stop_all(&self) {
-        let mut processes = self.processes.lock().unwrap();
-        for process in processes.drain(..) {
-            let pid = process.pid();
-            log::info!("Terminating process {}", pid);
+    /// Report that `name` isn't started because `dependency` isn't ready
+    fn dependency_failed(&self, name: &str, dependency: &str, reason: String) -> BackendError {
+        self.note(
+            log::Level::Warn,
+            &format!(
+                "Not starting {}: {} did not become ready ({})",
+                name, dependency, reason
+            ),
+        );
+        let payload = DependencyFailedPayload {
+            name: name.to_string(),
+            depends_on: dependency.to_string(),
+            reason: reason.clone(),
+        };
+        if let Err(e) = self.app.emit("backend://dependency-failed", payload) {
+            log::error!("Failed to emit backend://dependency-failed: {}", e);
+        }
+        BackendError::DependencyNotReady {
+            name: name.to_string(),
+            dependency: dependency.to_string(),
+            reason,
+        }
+    }
 
-            // Attempt to terminate any descendants spawned under this process BEFORE killing the parent
-            self.kill_descendants_best_effort(pid);
+    /// Allowlisted host variables the backend reads: its own settings, exchange and
+    /// model provider credentials, and proxies
+    fn backend_env() -> HashMap<String, String> {
+        // vars() panics on a variable that isn't valid Unicode; those are skipped
+        let env: HashMap<_, _> = std::env::vars_os()
+            .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)))
+            .filter(|(key, _)| {
+                ENV_ALLOWLIST.contains(&key.as_str())
+                    || ENV_ALLOWED_PREFIXES.iter().any(|p| key.starts_with(p))
+                    || ENV_ALLOWED_SUFFIXES.iter().any(|s| key.ends_with(s))
+            })
+            .collect();
 
-            // Use CommandChild's kill method
-            if let Err(e) = process.kill() {
-                log::error!("Failed to kill process {}: {}", pid, e);
-            } else {
-                log::info!("Process {} terminated", pid);
+        let mut keys: Vec<_> = env.keys().map(String::as_str).collect();
+        keys.sort_unstable();
+        log::info!("Backend environment: {}", keys.join(", "));
+        env
+    }
+
+    /// Delete backend logs in `log_dir` older than `max_age`; failures only cost disk space
+    fn prune_old_logs(log_dir: &Path, max_age: Duration) {
+        match log_file::prune(log_dir, max_age) {
+            Ok(removed) => {
+                for path in removed {
+                    log::info!("Removed old backend log {:?}", path);
+                }
             }
+            Err(e) => log::warn!("Failed to prune old backend logs in {:?}: {}", log_dir, e),
         }
     }
 
-    fn stream_backend_logs(&self, rx: Receiver<CommandEvent>) {
-        let log_path = self.log_dir.join("backend.log");
-        std::thread::spawn(move || Self::stream_to_file(rx, log_path));
+    /// The first of `backend_dir_candidates` that holds the Python project. If none
+    /// does, fails with the first existing candidate's contents, or with every
+    /// location tried when none exists.
+    fn resolve_backend_dir(app: &AppHandle<R>) -> Result<(&'static str, PathBuf), BackendError> {
+        Self::first_backend_dir(Self::backend_dir_candidates(app))
     }
 
-    fn stream_to_file(mut rx: Receiver<CommandEvent>, log_path: PathBuf) {
-        let mut file = match OpenOptions::new().create(true).append(true).open(&log_path) {
-            Ok(file) => file,
-            Err(err) => {
-                log::error!("Failed to open backend log file {:?}: {}", log_path, err);
-                return;
+    fn first_backend_dir(
+        candidates: Vec<(&'static str, PathBuf)>,
+    ) -> Result<(&'static str, PathBuf), BackendError> {
+        let mut tried = Vec::new();
+        let mut invalid = None;
+        for (source, path) in candidates {
+            // The resource root comes from the canonicalized exe path, which on
+            // Windows carries a `\\?\` prefix that Python and uv handle poorly; drop
+            // it where that doesn't change the meaning, fit_path puts it back for
+            // paths too long without it. Non-ASCII characters and spaces are kept
+            // as they are: the path goes to the process as an OsStr, never as text.
+            let path = dunce::simplified(&path).to_path_buf();
+            if tried.contains(&path) {
+                continue;
             }
-        };
+            tried.push(path.clone());
+            if !path.exists() {
+                log::info!("No backend at {:?} ({})", path, source);
+                continue;
+            }
+            match Self::validate_backend_dir(&path) {
+                Ok(()) => {
+                    log::info!("Using backend at {:?} ({})", path, source);
+                    return Ok((source, path));
+                }
+                Err(e) => {
+                    log::warn!("Skipping {:?} ({}): {}", path, source, e);
+                    invalid.get_or_insert(e);
+                }
+            }
+        }
+        Err(invalid.unwrap_or(BackendError::BackendDirMissing { tried }))
+    }
 
-        while let Some(event) = rx.blocking_recv() {
-            match event {
-                CommandEvent::Stdout(line) | CommandEvent::Stderr(line) => {
-                    let text = String::from_utf8_lossy(&line);
-                    if let Err(err) = writeln!(file, "{}", text.trim_end_matches('\n')) {
-                        log::error!("Failed to write backend log line: {}", err);
-                        break;
-                    }
+    /// Where the backend may be, in order of preference: the `TRADEBYWEEX_BACKEND_DIR`
+    /// override or its older name `VALUECELL_BACKEND_PATH`, the Tauri resource dir
+    /// and, on Linux, the resource layouts of deb, rpm and AppImage packages next
+    /// to the executable
+    fn backend_dir_candidates(app: &AppHandle<R>) -> Vec<(&'static str, PathBuf)> {
+        let mut candidates = Self::override_dir(|name| std::env::var_os(name))
+            .into_iter()
+            .collect::<Vec<_>>();
+        match app.path().resolve(".", BaseDirectory::Resource) {
+            Ok(resources) => candidates.push(("resource dir", resources.join("backend"))),
+            Err(e) => log::warn!("Failed to resolve resource root: {}", e),
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            let info = app.package_info();
+            let names = [info.name.as_str(), info.crate_name];
+            // <prefix>/bin/<app> with resources in <prefix>/lib/<name>[/resources]
+            let exe_dir = std::env::current_exe()
+                .ok()
+                .and_then(|exe| exe.parent().map(Path::to_path_buf));
+            if let Some(prefix) = exe_dir.as_deref().and_then(Path::parent) {
+                for name in names {
+                    let lib = prefix.join("lib").join(name);
+                    candidates.push(("executable-relative", lib.join("backend")));
+                    candidates.push(("executable-relative", lib.join("resources/backend")));
                 }
-                CommandEvent::Error(err) => {
-                    log::error!("Backend process error: {}", err);
-                    break;
+            }
+            // The mount point of a running AppImage
+            if let Some(app_dir) = std::env::var_os("APPDIR") {
+                for name in names {
+                    let lib = Path::new(&app_dir).join("usr/lib").join(name);
+                    candidates.push(("AppImage", lib.join("backend")));
                 }
-                CommandEvent::Terminated(_) => break,
-                _ => {}
             }
         }
+        candidates
     }
-}
 
-impl Drop for BackendManager {
-    fn drop(&mut self) {
-        self.stop_all();
+    /// The backend dir override and the variable it came from. The new name wins
+    /// when both are set; the old one is only looked at when the new one isn't.
+    fn override_dir(
+        var: impl Fn(&str) -> Option<std::ffi::OsString>,
+    ) -> Option<(&'static str, PathBuf)> {
+        let (source, path) = [BACKEND_DIR_ENV, LEGACY_BACKEND_DIR_ENV]
+            .into_iter()
+            .find_map(|name| {
+                var(name)
+                    .filter(|path| !path.is_empty())
+                    .map(|path| (name, path))
+            })?;
+        // A relative path would otherwise depend on where the app was started
+        // from, which differs between a terminal and the file manager
+        let path = std::path::absolute(&path).unwrap_or_else(|_| PathBuf::from(path));
+        Some((source, path))
+    }
+
+    /// Make sure the bundled directory is the Python project and not some other
+    /// folder the resource bundle picked up by mistake
+    fn validate_backend_dir(path: &Path) -> Result<(), BackendError> {
+        let mut found: Vec<String> = fs::read_dir(path)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        found.sort();
+        log::info!("Backend directory contains: {}", found.join(", "));
+
+        if path.join("pyproject.toml").is_file() || path.join("valuecell").is_dir() {
+            return Ok(());
+        }
+        Err(BackendError::BackendDirInvalid {
+            path: path.to_path_buf(),
+            found,
+        })
+    }
+
+    /// `path` in the form to hand to the backend when files up to `headroom`
+    /// characters deeper are created in it; see `long_path::fit`
+    fn fit_path(path: &Path, headroom: usize) -> Result<PathBuf, BackendError> {
+        #[cfg(windows)]
+        return crate::long_path::fit(path, headroom).ok_or_else(|| BackendError::PathTooLong {
+            path: path.to_path_buf(),
+        });
+
+        #[cfg(not(windows))]
+        {
+            let _ = headroom;
+            Ok(path.to_path_buf())
+        }
+    }
+
+    /// Arguments are passed without a shell, but reject anything that looks like an
+    /// attempt to chain commands. Spaces, quotes and non-ASCII text are fine: each
+    /// argument reaches the process as one OsStr.
+    fn check_arguments(args: &[String]) -> Result<(), BackendError> {
+        match args.iter().find(|arg| arg.contains(SHELL_METACHARACTERS)) {
+            Some(arg) => Err(BackendError::InvalidArgument(arg.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn spawn_failed(program: &'static str, e: tauri_plugin_shell::Error) -> BackendError {
+        BackendError::SpawnFailed {
+            program,
+            source: e.into(),
+        }
+    }
+
+    /// `spawn_failed` naming the working directory, which is the usual suspect
+    /// when the path has unusual characters
+    fn spawn_failed_in(
+        program: &'static str,
+        dir: &Path,
+        e: tauri_plugin_shell::Error,
+    ) -> BackendError {
+        BackendError::SpawnFailed {
+            program,
+            source: anyhow::Error::from(e).context(format!("working directory {:?}", dir)),
+        }
+    }
+
+    pub fn new(app: AppHandle<R>) -> Result<Self, BackendError> {
+        let startup_timeout =
+            Duration::from_secs(env_or(STARTUP_TIMEOUT_ENV, DEFAULT_STARTUP_TIMEOUT_SECS));
+        Self::with_timeouts(app, ShutdownTimeouts::default(), startup_timeout)
+    }
+
+    /// Create a manager with explicit shutdown step timeouts and the time a spawned
+    /// backend gets to become ready before `backend://startup-failed` is emitted
+    pub fn with_timeouts(
+        app: AppHandle<R>,
+        shutdown_timeouts: ShutdownTimeouts,
+        startup_timeout: Duration,
+    ) -> Result<Self, BackendError> {
+        let (source, found) = Self::resolve_backend_dir(&app)?;
+        let data_dir = app
+            .path()
+            .app_data_dir()
+            .context("Failed to get app data directory")?;
+        // An override points at a writable checkout, which is used as it is
+        let (backend_path, bundled_backend) =
+            if source == BACKEND_DIR_ENV || source == LEGACY_BACKEND_DIR_ENV {
+                (found, None)
+            } else {
+                let copy = data_dir.join(format!("backend-{}", app.package_info().version));
+                create_dir_all(&copy).map_err(|e| BackendError::BackendCopyFailed {
+                    from: found.clone(),
+                    to: copy.clone(),
+                    source: e,
+                })?;
+                (copy, Some(found))
+            };
+        let backend_path = Self::fit_path(&backend_path, BACKEND_PATH_HEADROOM)?;
+
+        let mut log_dir = app
+            .path()
+            .app_log_dir()
+            .context("Failed to get log directory")?
+            .join("backend");
+
+        // File logs are a convenience; without them output still reaches the app log
+        let log_dir_error = match create_dir_all(&log_dir) {
+            Ok(()) => match Self::fit_path(&log_dir, LOG_PATH_HEADROOM) {
+                Ok(fitted) => {
+                    log_dir = fitted;
+                    let max_age_days: u64 = env_or(LOG_MAX_AGE_ENV, DEFAULT_LOG_MAX_AGE_DAYS);
+                    Self::prune_old_logs(
+                        &log_dir,
+                        Duration::from_secs(max_age_days * 24 * 60 * 60),
+                    );
+                    None
+                }
+                Err(e) => {
+                    let reason = e.to_string();
+                    log::error!("{}; backend output goes to the app log only", reason);
+                    Some(reason)
+                }
+            },
+            Err(e) => {
+                let reason = format!("Failed to create log directory {:?}: {}", log_dir, e);
+                log::error!("{}; backend output goes to the app log only", reason);
+                Some(reason)
+            }
+        };
+
+        let pid_file = data_dir.join(PID_FILE);
+        let sync_marker = data_dir.join(SYNC_MARKER_FILE);
+        let maintenance_file = data_dir.join(MAINTENANCE_FILE);
+        let maintenance = MaintenanceSchedule::load(&maintenance_file).unwrap_or_else(|e| {
+            log::warn!("Ignoring maintenance schedule: {:#}", e);
+            MaintenanceSchedule::default()
+        });
+        let settings_file = data_dir.join(SETTINGS_FILE);
+        let launch_settings = LaunchSettings::load(&settings_file).unwrap_or_else(|e| {
+            log::warn!("Ignoring launch settings: {:#}", e);
+            LaunchSettings::default()
+        });
+
+        let preferred_port = env_or(PORT_ENV, DEFAULT_PORT);
+
+        log::info!("Backend path: {:?}", backend_path);
+        log::info!("Log directory: {:?}", log_dir);
+
+        Ok(Self {
+            state: Mutex::new(BackendState::Stopped),
+            last_error: Mutex::new(None),
+            last_exit: Mutex::new(None),
+            restart_policy: Mutex::new(env_or(RESTART_POLICY_ENV, RestartPolicy::default())),
+            restart_backoff: RestartBackoff::default(),
+            shutting_down: AtomicBool::new(false),
+            exiting: AtomicBool::new(false),
+            sync_child: Mutex::new(None),
+            shutdown_timeouts,
+            port: AtomicU16::new(preferred_port),
+            preferred_port,
+            log_slot: AtomicUsize::new(0),
+            health_check: HealthCheck::default(),
+            watchdog: HealthWatchdog::default(),
+            memory_watchdog: MemoryWatchdog::default(),
+            http: reqwest::Client::new(),
+            shutdown_path: std::env::var(SHUTDOWN_PATH_ENV)
+                .unwrap_or_else(|_| SHUTDOWN_PATH.to_string()),
+            quiesce_path: std::env::var(QUIESCE_PATH_ENV)
+                .unwrap_or_else(|_| QUIESCE_PATH.to_string()),
+            main_module: std::env::var(MAIN_MODULE_ENV)
+                .ok()
+                .filter(|module| !module.trim().is_empty())
+                .unwrap_or_else(|| MAIN_MODULE.to_string()),
+            startup_timeout,
+            spawn_attempts: env_or(SPAWN_ATTEMPTS_ENV, DEFAULT_SPAWN_ATTEMPTS).max(1),
+            spawn_retry_delay: Duration::from_millis(env_or(
+                SPAWN_RETRY_DELAY_ENV,
+                DEFAULT_SPAWN_RETRY_DELAY_MS,
+            )),
+            max_log_bytes: DEFAULT_MAX_LOG_BYTES,
+            max_log_files: DEFAULT_MAX_LOG_FILES,
+            timestamps: env_or(LOG_TIMESTAMPS_ENV, true),
+            extra_args: Mutex::new(Vec::new()),
+            env: Self::backend_env(),
+            auto_sync: !env_flag(SKIP_SYNC_ENV),
+            offline: env_flag(OFFLINE_ENV),
+            uv: Self::select_uv_sidecar(),
+            uv_resolved: Mutex::new(None),
+            sync_marker,
+            debug: env_flag(DEBUG_ENV),
+            log_levels: env_flag(LOG_LEVELS_ENV),
+            json_logs: env_flag(JSON_LOGS_ENV),
+            ready_banner: Self::ready_banner_pattern(),
+            last_output: Mutex::new(HashMap::new()),
+            bound_address: Mutex::new(None),
+            readiness: env_or(READINESS_ENV, Readiness::default()),
+            marker_seen: Mutex::new(None),
+            last_spawn: Mutex::new(None),
+            start_count: AtomicU32::new(0),
+            backend_path,
+            bundled_backend,
+            copy_lock: Mutex::new(()),
+            log_dir,
+            log_dir_error,
+            pid_file,
+            maintenance: Mutex::new(maintenance),
+            maintenance_file,
+            launch_settings: Mutex::new(launch_settings),
+            settings_file,
+            idle_timeout: {
+                let minutes: u64 = env_or(IDLE_SHUTDOWN_ENV, 0);
+                (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+            },
+            last_activity: Mutex::new(Instant::now()),
+            idle_stopped: AtomicBool::new(false),
+            supervisor: OnceLock::new(),
+            #[cfg(windows)]
+            job: crate::job_object::JobObject::kill_on_close()
+                .inspect_err(|e| log::warn!("Failed to create job object for the backend: {}", e))
+                .ok(),
+            app,
+        })
+    }
+
+    /// Pick the port for a fresh start. A backend left over from a crashed run
+    /// would hold the port we want, so it is reaped first.
+    fn claim_port(&self) {
+        self.reap_orphan();
+        // A foreign process keeps the port; select_port then moves to a free one
+        if let Err(e) = self.free_port(self.preferred_port) {
+            log::warn!("{}", e.report());
+        }
+        self.port
+            .store(Self::select_port(self.preferred_port), Ordering::SeqCst);
+    }
+
+    /// Use `preferred` if it can be bound, otherwise let the OS pick a free port
+    fn select_port(preferred: u16) -> u16 {
+        if TcpListener::bind((Ipv4Addr::LOCALHOST, preferred)).is_ok() {
+            log::info!("Backend port: {}", preferred);
+            return preferred;
+        }
+
+        match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).and_then(|l| l.local_addr()) {
+            Ok(addr) => {
+                log::warn!(
+                    "Port {} is in use, backend will use free port {}",
+                    preferred,
+                    addr.port()
+                );
+                addr.port()
+            }
+            Err(e) => {
+                log::warn!(
+                    "Port {} is in use and no free port was found ({}), trying it anyway",
+                    preferred,
+                    e
+                );
+                preferred
+            }
+        }
+    }
+
+    /// Where the shell plugin looks for the sidecar `name`: next to the app executable
+    fn sidecar_path(name: &str) -> Result<PathBuf> {
+        let exe = std::env::current_exe().context("Failed to locate the app executable")?;
+        let dir = exe
+            .parent()
+            .context("App executable has no parent directory")?;
+        Ok(dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+    }
+
+    /// The bundled uv for this machine: `uv-<arch>` when the bundle has one for
+    /// the architecture the machine really has, otherwise `uv`. Warns when it
+    /// runs translated, which also happens when the whole app does.
+    fn select_uv_sidecar() -> UvSidecar {
+        let native = arch::native();
+        if arch::translated() {
+            log::warn!(
+                "⚠️ This {} build of ValueCell runs translated on a {} machine (e.g. under \
+                 Rosetta). It is slower and native extensions like numpy may crash; install \
+                 the {} build instead.",
+                std::env::consts::ARCH,
+                native.map_or("different", Arch::name),
+                native.map_or("native", Arch::name),
+            );
+        }
+
+        let native_name = native.map(|arch| format!("uv-{}", arch.name()));
+        let name = native_name
+            .filter(|name| Self::sidecar_path(name).is_ok_and(|path| path.is_file()))
+            .unwrap_or_else(|| "uv".to_string());
+        let mut sidecar = UvSidecar {
+            name,
+            python_platform: None,
+        };
+        let Ok(path) = Self::sidecar_path(&sidecar.name) else {
+            return sidecar;
+        };
+        let archs = match arch::of_binary(&path) {
+            Ok(archs) => archs,
+            // Another uv is looked for when starting
+            Err(_) => return sidecar,
+        };
+        log::info!("uv sidecar {:?} is built for {:?}", path, archs);
+        match (native, archs.first()) {
+            (Some(native), Some(&runs_as)) if !archs.contains(&native) => {
+                log::warn!(
+                    "⚠️ uv sidecar {:?} is built for {} but this machine is {}; uv and the \
+                     backend run translated, pinning uv sync to {}",
+                    path,
+                    runs_as.name(),
+                    native.name(),
+                    runs_as.uv_platform()
+                );
+                sidecar.python_platform = Some(runs_as.uv_platform());
+            }
+            _ => {}
+        }
+        sidecar
+    }
+
+    /// The uv to run, found once and then reused: the bundled sidecar, else `uv`
+    /// on PATH, else the `uv_path` launch setting. Each must answer `--version`.
+    /// Fails with `BackendError::UvMissing` naming everything tried.
+    fn uv(&self) -> Result<UvProgram, BackendError> {
+        if let Some(uv) = lock_or_recover(&self.uv_resolved).clone() {
+            return Ok(uv);
+        }
+
+        let mut tried = Vec::new();
+        let sidecar = Self::sidecar_path(&self.uv.name)?;
+        let candidates = [
+            ("bundled sidecar", UvProgram::Sidecar(self.uv.name.clone())),
+            ("PATH", UvProgram::System(PathBuf::from("uv"))),
+        ]
+        .into_iter()
+        .chain(
+            self.launch_settings()
+                .uv_path
+                .map(|path| ("launch settings", UvProgram::System(path))),
+        );
+        for (source, uv) in candidates {
+            let what = match &uv {
+                UvProgram::Sidecar(_) => format!("{} {:?}", source, sidecar),
+                UvProgram::System(path) => format!("{:?} from {}", path, source),
+            };
+            if matches!(uv, UvProgram::Sidecar(_)) && !sidecar.is_file() {
+                log::warn!("uv {} is missing", what);
+                tried.push(format!("{}: missing", what));
+                continue;
+            }
+            match self.uv_version(&uv) {
+                Ok(version) => {
+                    log::info!("Using uv {} ({})", what, version);
+                    *lock_or_recover(&self.uv_resolved) = Some(uv.clone());
+                    return Ok(uv);
+                }
+                Err(reason) => {
+                    log::warn!("uv {} doesn't work: {}", what, reason);
+                    tried.push(format!("{}: {}", what, reason));
+                }
+            }
+        }
+        log::error!("❌ No working uv found");
+        Err(BackendError::UvMissing { tried })
+    }
+
+    /// Output of `uv --version`
+    fn uv_version(&self, uv: &UvProgram) -> Result<String, String> {
+        let command = self.command_for(uv).map_err(|e| e.to_string())?;
+        let output = tauri::async_runtime::block_on(command.args(["--version"]).output())
+            .map_err(|e| e.to_string())?;
+        if !output.status.success() {
+            return Err(format!("--version exited with {:?}", output.status.code()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn command_for(&self, uv: &UvProgram) -> Result<Command, tauri_plugin_shell::Error> {
+        match uv {
+            UvProgram::Sidecar(name) => self.app.shell().sidecar(name),
+            UvProgram::System(path) => Ok(self.app.shell().command(path)),
+        }
+    }
+
+    /// A command running the resolved uv
+    fn uv_command(&self) -> Result<Command, BackendError> {
+        let uv = self.uv()?;
+        self.command_for(&uv)
+            .map_err(|e| Self::spawn_failed("uv", e))
+            .inspect_err(|_| self.forget_uv())
+    }
+
+    /// Report a backend directory that went away since startup by its path,
+    /// rather than as the spawn error it would cause as working directory
+    fn check_backend_dir(&self) -> Result<(), BackendError> {
+        if self.backend_path.is_dir() {
+            return Ok(());
+        }
+        log::error!("❌ Backend directory {:?} is gone", self.backend_path);
+        Err(BackendError::BackendDirMissing {
+            tried: vec![self.backend_path.clone()],
+        })
+    }
+
+    /// Look for uv again on the next start, e.g. after it failed to spawn
+    fn forget_uv(&self) {
+        *lock_or_recover(&self.uv_resolved) = None;
+    }
+
+    /// Bring the writable copy of the backend up to date with the bundle; does
+    /// nothing when running from the `TRADEBYWEEX_BACKEND_DIR` override
+    fn refresh_backend_copy(&self) -> Result<(), BackendError> {
+        let Some(bundled) = &self.bundled_backend else {
+            return Ok(());
+        };
+        let _copying = lock_or_recover(&self.copy_lock);
+        let started = Instant::now();
+        match backend_copy::sync(bundled, &self.backend_path) {
+            Ok(None) => log::info!("Backend copy in {:?} is up to date", self.backend_path),
+            Ok(Some(report)) => log::info!(
+                "✓ Backend copied to {:?} in {:?}: {} files copied, {} removed, {} unchanged",
+                self.backend_path,
+                started.elapsed(),
+                report.copied,
+                report.removed,
+                report.unchanged
+            ),
+            Err(e) => {
+                return Err(BackendError::BackendCopyFailed {
+                    from: bundled.clone(),
+                    to: self.backend_path.clone(),
+                    source: e,
+                })
+            }
+        }
+        Ok(())
+    }
+
+    fn install_dependencies(&self) -> Result<(), BackendError> {
+        if self.offline && self.venv_in_sync(self.lock_stamp().as_deref()) {
+            log::info!("Offline and the venv matches uv.lock, skipping uv sync");
+            return Ok(());
+        }
+        self.sync_dependencies(false)
+    }
+
+    /// Run uv sync, with `reinstall` rebuilding every package of the venv
+    fn sync_dependencies(&self, reinstall: bool) -> Result<(), BackendError> {
+        let lock_stamp = self.lock_stamp();
+        self.set_state(BackendState::Installing);
+        // Until this sync succeeds the venv may be half installed
+        if let Err(e) = fs::remove_file(&self.sync_marker) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to remove {:?}: {}", self.sync_marker, e);
+            }
+        }
+
+        let mut args = vec!["sync", "--frozen"];
+        if self.offline {
+            args.push("--offline");
+        }
+        if reinstall {
+            args.push("--reinstall");
+        }
+        let uv = self
+            .check_backend_dir()
+            .and_then(|()| self.uv_command())
+            .inspect_err(|e| self.set_failed(e.report()))?;
+        // The bundled uv's architecture is known, a system one's isn't
+        if let (Some(platform), Ok(UvProgram::Sidecar(_))) = (self.uv.python_platform, self.uv()) {
+            args.extend(["--python-platform", platform]);
+        }
+        log::info!("Command: uv {}", args.join(" "));
+        let (rx, child) = uv
+            .args(args)
+            .current_dir(&self.backend_path)
+            .spawn()
+            .map_err(|e| Self::spawn_failed_in("uv sync", &self.backend_path, e))
+            .inspect_err(|e| {
+                self.forget_uv();
+                self.set_failed(e.report());
+            })?;
+        *lock_or_recover(&self.sync_child) = Some(child);
+        // cancel_start may have looked for the child before it was stored
+        if self.exiting.load(Ordering::SeqCst) {
+            self.kill_sync();
+        }
+        let sync_log_path = self.log_dir.join(UV_SYNC_LOG_FILE);
+        let mut sync_log = self.open_log(sync_log_path.clone());
+        let exit = Self::wait_until_terminated(rx, |stream, line| {
+            if let Some(file) = sync_log.as_mut() {
+                if let Err(err) = file.write_line(line) {
+                    log::error!("Failed to write uv sync log line: {}", err);
+                    sync_log = None;
+                }
+            }
+            let payload = DepsProgressPayload {
+                stream,
+                line: line.to_string(),
+            };
+            if let Err(e) = self.app.emit("backend://deps-progress", payload) {
+                log::warn!("Failed to emit backend://deps-progress: {}", e);
+            }
+        });
+
+        lock_or_recover(&self.sync_child).take();
+        if self.exiting.load(Ordering::SeqCst) {
+            log::info!("uv sync cancelled, the app is exiting");
+            return Err(BackendError::StartCancelled);
+        }
+
+        let (code, signal) = exit.map_or((None, None), |exit| (exit.code, exit.signal));
+        if code != Some(0) {
+            let log_tail = log_file::tail(&sync_log_path, DEPS_SYNC_FAILED_TAIL_LINES)
+                .unwrap_or_else(|e| {
+                    log::warn!("Failed to read {:?}: {}", sync_log_path, e);
+                    Vec::new()
+                });
+            let error = BackendError::DepsSyncFailed {
+                code,
+                signal,
+                log_tail,
+            };
+            self.set_failed(error.report());
+            return Err(error);
+        }
+
+        log::info!("✓ Dependencies installed/verified");
+        if let Some(stamp) = lock_stamp {
+            let written = self
+                .sync_marker
+                .parent()
+                .map_or(Ok(()), create_dir_all)
+                .and_then(|()| fs::write(&self.sync_marker, stamp));
+            if let Err(e) = written {
+                log::warn!("Failed to write {:?}: {}", self.sync_marker, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Kill the uv sync in progress along with what it spawned, e.g. builds of
+    /// source packages
+    fn kill_sync(&self) {
+        let Some(child) = lock_or_recover(&self.sync_child).take() else {
+            return;
+        };
+        let pid = child.pid();
+        let tree = process_tree::descendants(pid);
+        log::warn!(
+            "Killing uv sync (process {}) and descendants {:?}",
+            pid,
+            tree
+        );
+        self.force_kill_pids(&tree);
+        if let Err(e) = child.kill() {
+            log::warn!("Failed to kill uv sync (process {}): {}", pid, e);
+        }
+    }
+
+    /// Give up on starting the backend for the rest of the app's life: a uv sync
+    /// in progress is killed and nothing is spawned afterwards. For an app that
+    /// is about to exit; what is already running is left to `shutdown`.
+    pub fn cancel_start(&self) {
+        if self.exiting.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.shutting_down.store(true, Ordering::SeqCst);
+        self.kill_sync();
+    }
+
+    /// Size and modification time of uv.lock, or `None` when it can't be read
+    fn lock_stamp(&self) -> Option<String> {
+        let metadata = fs::metadata(self.backend_path.join("uv.lock")).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(format!("{}:{}", metadata.len(), modified.as_nanos()))
+    }
+
+    /// Whether the venv exists and was last synced with the current uv.lock
+    fn venv_in_sync(&self, lock_stamp: Option<&str>) -> bool {
+        let Some(stamp) = lock_stamp else {
+            return false;
+        };
+        self.backend_path.join(".venv").is_dir()
+            && fs::read_to_string(&self.sync_marker).is_ok_and(|synced| synced == stamp)
+    }
+
+    /// Start the API server, then the configured services
+    pub fn start_all(&self, processes: &mut ProcessRegistry) -> Result<(), BackendError> {
+        self.start_with_args(processes, &[])?;
+        self.start_services();
+        Ok(())
+    }
+
+    /// Start the backend with `extra` appended to its command line, e.g.
+    /// `--log-level debug`. The arguments stay in effect for later restarts.
+    pub fn start_with_args(
+        &self,
+        processes: &mut ProcessRegistry,
+        extra: &[String],
+    ) -> Result<(), BackendError> {
+        if self.exiting.load(Ordering::SeqCst) {
+            return Err(BackendError::StartCancelled);
+        }
+        if let Some(pid) = processes.primary_pid() {
+            return Err(BackendError::AlreadyRunning { pid: Some(pid) });
+        }
+
+        Self::check_arguments(extra)?;
+        *lock_or_recover(&self.extra_args) = extra.to_vec();
+
+        self.uv().inspect_err(|e| self.set_failed(e.report()))?;
+        self.claim_port();
+        self.refresh_backend_copy()
+            .inspect_err(|e| self.set_failed(e.report()))?;
+        if self.auto_sync {
+            self.install_dependencies()?;
+        } else {
+            log::info!("Skipping uv sync ({} is set)", SKIP_SYNC_ENV);
+        }
+        if self.exiting.load(Ordering::SeqCst) {
+            return Err(BackendError::StartCancelled);
+        }
+        self.shutting_down.store(false, Ordering::SeqCst);
+
+        self.spawn_with_retries(processes)
+            .inspect_err(|e| self.set_failed(e.report()))?;
+        // The app started exiting during the spawn, after cancel_start looked
+        if self.exiting.load(Ordering::SeqCst) {
+            self.stop_tracked(processes)?;
+            return Err(BackendError::StartCancelled);
+        }
+
+        Ok(())
+    }
+
+    /// Sync the venv with uv.lock again, e.g. after it was damaged, streaming
+    /// the output as `backend://deps-progress`. Refused while a backend process
+    /// runs, since packages it has loaded would be replaced under it.
+    pub fn resync_dependencies(
+        &self,
+        processes: &mut ProcessRegistry,
+        reinstall: bool,
+    ) -> Result<(), BackendError> {
+        if !processes.is_empty() {
+            return Err(BackendError::AlreadyRunning {
+                pid: processes.primary_pid(),
+            });
+        }
+
+        log::info!(
+            "Re-syncing backend dependencies{}",
+            if reinstall { " (reinstall)" } else { "" }
+        );
+        self.uv().inspect_err(|e| self.set_failed(e.report()))?;
+        self.refresh_backend_copy()
+            .inspect_err(|e| self.set_failed(e.report()))?;
+        self.sync_dependencies(reinstall)?;
+        self.set_state(BackendState::Stopped);
+        Ok(())
+    }
+
+    /// Like `start_with_args`, but a running backend is stopped first instead of
+    /// being reported as `AlreadyRunning`
+    pub fn start_forced(
+        &self,
+        processes: &mut ProcessRegistry,
+        extra: &[String],
+    ) -> Result<(), BackendError> {
+        let stopped = self.stop_tracked(processes)?;
+        if !stopped.is_empty() {
+            log::info!(
+                "Stopped backend processes {:?} before a forced start",
+                stopped
+            );
+            std::thread::sleep(RESTART_SETTLE_DELAY);
+        }
+        self.start_with_args(processes, extra)
+    }
+
+    /// Start the backend unless one is already tracked, returning its PID either way
+    pub fn start_backend(&self, processes: &mut ProcessRegistry) -> Result<u32, BackendError> {
+        if let Some(pid) = processes.primary_pid() {
+            log::info!("Backend already running as process {}", pid);
+            return Ok(pid);
+        }
+
+        match self.start_all(processes) {
+            Err(BackendError::AlreadyRunning { pid: Some(pid) }) => return Ok(pid),
+            result => result?,
+        }
+        processes
+            .primary_pid()
+            .ok_or_else(|| anyhow!("Backend exited right after starting").into())
+    }
+
+    /// Free the port and spawn the backend, retrying a failure such as a port that
+    /// is still held for a moment up to `spawn_attempts` times
+    fn spawn_with_retries(&self, processes: &mut ProcessRegistry) -> Result<u32, BackendError> {
+        let mut attempt = 1;
+        loop {
+            self.note(
+                log::Level::Info,
+                &format!(
+                    "Starting backend (attempt {}/{})",
+                    attempt, self.spawn_attempts
+                ),
+            );
+            let result = self
+                .free_port(self.port())
+                .and_then(|()| self.spawn_and_track(processes));
+            match result {
+                Ok(pid) => return Ok(pid),
+                Err(e)
+                    if attempt < self.spawn_attempts
+                        && !self.shutting_down.load(Ordering::SeqCst) =>
+                {
+                    self.note(
+                        log::Level::Warn,
+                        &format!(
+                            "Backend start attempt {}/{} failed, retrying in {:?}: {}",
+                            attempt,
+                            self.spawn_attempts,
+                            self.spawn_retry_delay,
+                            e.report()
+                        ),
+                    );
+                    std::thread::sleep(self.spawn_retry_delay);
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Spawn the backend, attach log streaming and register it in the process list.
+    /// Callers mark the backend as failed when this errors.
+    fn spawn_and_track(&self, processes: &mut ProcessRegistry) -> Result<u32, BackendError> {
+        self.set_state(BackendState::Starting);
+        let extra_args = lock_or_recover(&self.extra_args).clone();
+        let logs = self.backend_log_files(self.log_slot.load(Ordering::SeqCst));
+        let pid = self.launch(
+            processes,
+            BACKEND_PROCESS_NAME,
+            &self.main_module,
+            &extra_args,
+            Some(self.port()),
+            logs,
+        )?;
+        self.write_pid_file(pid);
+        if self.start_count.load(Ordering::SeqCst) > 0 {
+            processes.record_restart(BACKEND_PROCESS_NAME);
+        }
+        *lock_or_recover(&self.last_spawn) = Some(SpawnRecord {
+            pid,
+            at: Instant::now(),
+            timestamp: unix_millis(),
+        });
+        self.start_count.fetch_add(1, Ordering::SeqCst);
+        self.write_stats();
+        self.watch_readiness(processes, pid);
+
+        Ok(pid)
+    }
+
+    /// `name (pid N)` of a tracked process, `process N` otherwise
+    fn process_label(&self, pid: u32) -> String {
+        match self.tracked().name_of(pid) {
+            Some(name) => format!("{} (pid {})", name, pid),
+            None => format!("process {}", pid),
+        }
+    }
+
+    /// Name and metadata of every tracked process, serving one first
+    pub fn list_processes(&self) -> Vec<ProcessInfo> {
+        self.tracked().list()
+    }
+
+    /// PIDs of the tracked processes, in start order
+    pub fn backend_pids(&self) -> Vec<u32> {
+        self.tracked().pids()
+    }
+
+    fn port(&self) -> u16 {
+        self.port.load(Ordering::SeqCst)
+    }
+
+    /// URL of `path` on the backend that is currently serving
+    fn backend_url(&self, path: &str) -> String {
+        let serving = self.tracked().primary_pid();
+        // The server binds all interfaces, so only the port of the banner is useful here
+        let port = lock_or_recover(&self.bound_address)
+            .as_ref()
+            .filter(|a| Some(a.pid) == serving)
+            .map_or(self.port(), |a| a.port);
+        Self::url_on(port, path)
+    }
+
+    fn url_on(port: u16, path: &str) -> String {
+        format!("http://127.0.0.1:{}{}", port, path)
+    }
+
+    /// Base URL of the API the frontend should use
+    pub fn api_url(&self) -> String {
+        self.backend_url(API_PREFIX)
+    }
+
+    fn health_url(&self) -> String {
+        self.backend_url(&self.health_check.path)
+    }
+
+    /// Poll `url` until it answers 200, the attempt budget is used up or
+    /// `timeout` elapses
+    fn wait_for_ready_at(&self, url: String, timeout: Duration) -> Result<()> {
+        let check = self.health_check.clone();
+        tauri::async_runtime::block_on(Self::probe_health(&self.http, url, check, timeout))
+    }
+
+    async fn probe_health(
+        client: &reqwest::Client,
+        url: String,
+        check: HealthCheck,
+        timeout: Duration,
+    ) -> Result<()> {
+        let deadline = Instant::now() + timeout;
+        let mut last_error = String::from("no probe was sent");
+        for attempt in 1..=check.max_attempts {
+            match Self::probe_once(client, &url, check.request_timeout).await {
+                Ok(()) => {
+                    log::info!("Health check passed after {} attempt(s)", attempt);
+                    return Ok(());
+                }
+                Err(e) => last_error = e,
+            }
+
+            if Instant::now() >= deadline {
+                break;
+            }
+            tokio::time::sleep(check.interval).await;
+        }
+
+        Err(anyhow!(
+            "Backend not ready at {} (last error: {})",
+            url,
+            last_error
+        ))
+    }
+
+    async fn probe_once(
+        client: &reqwest::Client,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<(), String> {
+        match client.get(url).timeout(timeout).send().await {
+            Ok(response) if response.status() == StatusCode::OK => Ok(()),
+            Ok(response) => Err(format!("HTTP {}", response.status())),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Send a single health probe to the running backend. Always passes with
+    /// `Readiness::LogMarker`, which means there is no endpoint to probe.
+    fn check_health(&self) -> Result<(), String> {
+        if matches!(self.readiness, Readiness::LogMarker(_)) {
+            return Ok(());
+        }
+        let url = self.health_url();
+        tauri::async_runtime::block_on(Self::probe_once(
+            &self.http,
+            &url,
+            self.health_check.request_timeout,
+        ))
+    }
+
+    /// Probe a running backend periodically and react when it stops answering
+    fn watch_health(&self, pid: u32) {
+        if matches!(self.readiness, Readiness::LogMarker(_)) {
+            log::info!(
+                "Readiness is {}, not probing {} over HTTP",
+                self.readiness,
+                pid
+            );
+            return;
+        }
+        let watchdog = self.watchdog;
+        let mut failures = 0;
+        loop {
+            std::thread::sleep(watchdog.interval);
+            if !self.is_tracked(pid) || self.shutting_down.load(Ordering::SeqCst) {
+                return;
+            }
+
+            let last_error = match self.check_health() {
+                Ok(()) => {
+                    if failures > 0 {
+                        log::info!("Backend process {} is healthy again", pid);
+                    }
+                    failures = 0;
+                    continue;
+                }
+                Err(e) => e,
+            };
+            if self.is_silent(pid, watchdog.silence_timeout) {
+                self.handle_wedged(pid, last_error);
+                return;
+            }
+            failures += 1;
+            log::warn!(
+                "Health check {}/{} for backend process {} failed: {}",
+                failures,
+                watchdog.failure_threshold,
+                pid,
+                last_error
+            );
+            if failures < watchdog.failure_threshold {
+                continue;
+            }
+
+            log::error!(
+                "❌ Backend {} unresponsive after {} failed health checks",
+                self.process_label(pid),
+                failures
+            );
+            let payload = UnhealthyPayload {
+                pid,
+                failures,
+                last_error,
+                restarting: watchdog.auto_restart,
+            };
+            if let Err(e) = self.app.emit("backend://unhealthy", payload) {
+                log::error!("Failed to emit backend://unhealthy: {}", e);
+            }
+
+            if !watchdog.auto_restart {
+                // Watch-only: report again after another full round of failures
+                failures = 0;
+                continue;
+            }
+            self.note(
+                log::Level::Warn,
+                &format!("Backend process {} is unresponsive, restarting it", pid),
+            );
+            self.notify(supervisor::Command::Respawn { pid });
+            return;
+        }
+    }
+
+    /// Whether `pid` produced no output for longer than `timeout`
+    fn is_silent(&self, pid: u32, timeout: Option<Duration>) -> bool {
+        let Some(timeout) = timeout else {
+            return false;
+        };
+        let since = lock_or_recover(&self.last_output).get(&pid).copied();
+        let since = since.or_else(|| (*lock_or_recover(&self.last_spawn)).map(|s| s.at));
+        since.is_some_and(|at| at.elapsed() >= timeout)
+    }
+
+    /// Record a snapshot of a wedged backend and have the supervisor kill it. The
+    /// process stays in the process list, so `handle_exit` treats it as a crash and
+    /// applies the restart policy.
+    fn handle_wedged(&self, pid: u32, last_error: String) {
+        let silent_secs = lock_or_recover(&self.last_output)
+            .get(&pid)
+            .map_or(0, |at| at.elapsed().as_secs());
+        let uptime_secs = (*lock_or_recover(&self.last_spawn))
+            .filter(|s| s.pid == pid)
+            .map(|s| s.at.elapsed().as_secs());
+        let log_tail = log_file::tail(&self.stderr_log_path(), WEDGED_SNAPSHOT_LINES)
+            .unwrap_or_else(|e| vec![format!("<log unavailable: {}>", e)]);
+        let snapshot = WedgedPayload {
+            pid,
+            silent_secs,
+            uptime_secs,
+            last_error,
+            log_tail,
+            timestamp: unix_millis(),
+        };
+
+        log::error!(
+            "❌ Backend process {} is wedged: no output for {}s and health check failed: {}",
+            pid,
+            silent_secs,
+            snapshot.last_error
+        );
+        let path = self.log_dir.join(WEDGED_SNAPSHOT_FILE);
+        let written = serde_json::to_vec_pretty(&snapshot)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(&path, json));
+        if let Err(e) = written {
+            log::warn!("Failed to write {:?}: {}", path, e);
+        }
+        if let Err(e) = self.app.emit("backend://wedged", snapshot) {
+            log::error!("Failed to emit backend://wedged: {}", e);
+        }
+
+        self.note(
+            log::Level::Warn,
+            &format!(
+                "Backend process {} is wedged, killing it (snapshot in {:?})",
+                pid, path
+            ),
+        );
+        self.notify(supervisor::Command::Kill { pid });
+    }
+
+    /// Kill a wedged backend outright: a graceful exit would report code 0 and dodge
+    /// the restart policy
+    pub(crate) fn kill_wedged(&self, processes: &ProcessRegistry, pid: u32) {
+        if !processes.contains(pid) {
+            return;
+        }
+        if let Err(e) = tauri::async_runtime::block_on(self.force_kill_pid(pid)) {
+            log::error!("Failed to kill wedged backend process {}: {:#}", pid, e);
+        }
+    }
+
+    /// Replace the process `pid` with a fresh one
+    pub(crate) fn respawn(&self, processes: &mut ProcessRegistry, pid: u32) {
+        let Some(child) = processes.take(pid) else {
+            // Already stopped or replaced by the time the watchdog's request came in
+            return;
+        };
+        let deadline = Instant::now() + self.shutdown_timeouts.deadline;
+        if let Err(e) = tauri::async_runtime::block_on(self.terminate(child, deadline)) {
+            log::error!("Failed to stop backend process {}: {:#}", pid, e);
+        }
+        std::thread::sleep(RESTART_SETTLE_DELAY);
+        self.spawn_replacement(processes);
+    }
+
+    /// Spawn a backend in place of one that was stopped or died. Skipped once a
+    /// primary is tracked again, e.g. after a start queued during the backoff.
+    pub(crate) fn spawn_replacement(&self, processes: &mut ProcessRegistry) {
+        if self.exiting.load(Ordering::SeqCst) || self.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+        if let Some(pid) = processes.primary_pid() {
+            log::info!("Not restarting backend: process {} is already running", pid);
+            return;
+        }
+
+        match self.spawn_and_track(processes) {
+            Ok(new_pid) => self.note(
+                log::Level::Info,
+                &format!("Backend restarted as process {}", new_pid),
+            ),
+            Err(e) => {
+                self.note(
+                    log::Level::Error,
+                    &format!("Failed to restart backend: {}", e.report()),
+                );
+                self.set_failed(e.report());
+            }
+        }
+    }
+
+    /// Tell the supervisor whether `pid` printed its startup banner and the health
+    /// endpoint answers before the startup timeout
+    fn watch_readiness(&self, processes: &ProcessRegistry, pid: u32) {
+        let Some(exited) = processes.exit_watch(pid) else {
+            return;
+        };
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            let Some(manager) = app.try_state::<Self>() else {
+                return;
+            };
+            // Measured from the spawn, so dependency installation never counts against it
+            let deadline = Instant::now() + manager.startup_timeout;
+            match manager.wait_until_ready(pid, &exited, manager.health_url(), deadline) {
+                Ok(()) => manager.notify(supervisor::Command::Ready { pid }),
+                Err(e) => manager.notify(supervisor::Command::StartupFailed {
+                    pid,
+                    reason: format!("startup timeout: {e:#}"),
+                }),
+            }
+        });
+    }
+
+    /// Mark `pid` as running and start watching it, unless it was stopped or
+    /// replaced while it started up
+    pub(crate) fn mark_ready(&self, processes: &mut ProcessRegistry, pid: u32) {
+        if !processes.contains(pid) {
+            return;
+        }
+        log::info!("✓ Backend process {} is ready", pid);
+        processes.set_state(pid, BackendState::Running);
+        self.transition(BackendState::Running, Some(pid), None);
+        self.spawn_health_watch(pid);
+    }
+
+    /// Run `watch_health` for `pid` on its own thread
+    fn spawn_health_watch(&self, pid: u32) {
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            if let Some(manager) = app.try_state::<Self>() {
+                manager.watch_health(pid);
+            }
+        });
+        self.spawn_memory_watch(pid);
+    }
+
+    /// Check on its own thread that `pid` still exists, until it is no longer
+    /// tracked or the backend is shutting down
+    fn spawn_heartbeat(&self, pid: u32) {
+        let Some(interval) = self.watchdog.heartbeat else {
+            return;
+        };
+        let app = self.app.clone();
+        let started = Instant::now();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(interval);
+            let Some(manager) = app.try_state::<Self>() else {
+                return;
+            };
+            if !manager.is_tracked(pid) || manager.shutting_down.load(Ordering::SeqCst) {
+                return;
+            }
+            if process_tree::process_alive(pid) {
+                log::info!(
+                    "Heartbeat: {} alive, up {}s",
+                    manager.process_label(pid),
+                    started.elapsed().as_secs()
+                );
+                continue;
+            }
+
+            // Normally the exit event handles it; this catches one that never arrives
+            std::thread::sleep(HEARTBEAT_EXIT_GRACE);
+            if !manager.is_tracked(pid) || manager.shutting_down.load(Ordering::SeqCst) {
+                return;
+            }
+            manager.note(
+                log::Level::Warn,
+                &format!(
+                    "Heartbeat: {} vanished without an exit event, e.g. killed by the OOM killer",
+                    manager.process_label(pid)
+                ),
+            );
+            let exit = TerminatedPayload {
+                code: None,
+                signal: None,
+            };
+            manager.notify(supervisor::Command::Exited {
+                pid,
+                exit,
+                uptime: started.elapsed(),
+            });
+            return;
+        });
+    }
+
+    /// Run `watch_memory` for `pid` on its own thread when a ceiling is configured
+    fn spawn_memory_watch(&self, pid: u32) {
+        if !self.memory_watchdog.enabled() {
+            return;
+        }
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            if let Some(manager) = app.try_state::<Self>() {
+                manager.watch_memory(pid);
+            }
+        });
+    }
+
+    /// Sample the memory of `pid` and its descendants, warning above the soft
+    /// ceiling and restarting the backend above the hard one
+    fn watch_memory(&self, pid: u32) {
+        let watchdog = self.memory_watchdog;
+        let mut warned = false;
+        loop {
+            std::thread::sleep(watchdog.interval);
+            if !self.is_tracked(pid) || self.shutting_down.load(Ordering::SeqCst) {
+                return;
+            }
+            let Some(used_bytes) = process_tree::tree_memory_bytes(pid) else {
+                continue;
+            };
+
+            if let Some(limit_bytes) = watchdog.limit_bytes.filter(|&limit| used_bytes > limit) {
+                self.handle_memory_limit(pid, used_bytes, limit_bytes);
+                return;
+            }
+            match watchdog.warn_bytes {
+                Some(warn_bytes) if used_bytes > warn_bytes => {
+                    if !warned {
+                        log::warn!(
+                            "Backend process {} uses {} MB, above the {} MB warning level",
+                            pid,
+                            used_bytes / (1024 * 1024),
+                            warn_bytes / (1024 * 1024)
+                        );
+                        let payload = MemoryPayload {
+                            pid,
+                            used_bytes,
+                            threshold_bytes: warn_bytes,
+                        };
+                        if let Err(e) = self.app.emit("backend://memory-warning", payload) {
+                            log::error!("Failed to emit backend://memory-warning: {}", e);
+                        }
+                    }
+                    warned = true;
+                }
+                _ => warned = false,
+            }
+        }
+    }
+
+    fn handle_memory_limit(&self, pid: u32, used_bytes: u64, limit_bytes: u64) {
+        log::error!(
+            "❌ Backend process {} uses {} MB, above the {} MB limit",
+            pid,
+            used_bytes / (1024 * 1024),
+            limit_bytes / (1024 * 1024)
+        );
+        let timestamp = unix_millis();
+        let incident = MemoryIncident {
+            pid,
+            used_bytes,
+            limit_bytes,
+            uptime_secs: (*lock_or_recover(&self.last_spawn))
+                .filter(|s| s.pid == pid)
+                .map(|s| s.at.elapsed().as_secs()),
+            timestamp,
+            log_tail: log_file::tail(&self.stderr_log_path(), WEDGED_SNAPSHOT_LINES)
+                .unwrap_or_default(),
+        };
+        let path = self
+            .log_dir
+            .join(format!("memory-incident-{}.json", timestamp));
+        let written = serde_json::to_vec_pretty(&incident)
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(&path, json));
+        if let Err(e) = written {
+            log::warn!("Failed to write {:?}: {}", path, e);
+        }
+
+        let payload = MemoryPayload {
+            pid,
+            used_bytes,
+            threshold_bytes: limit_bytes,
+        };
+        if let Err(e) = self.app.emit("backend://memory-limit", payload) {
+            log::error!("Failed to emit backend://memory-limit: {}", e);
+        }
+        self.note(
+            log::Level::Warn,
+            &format!(
+                "Backend process {} exceeded the memory limit, restarting it (report in {:?})",
+                pid, path
+            ),
+        );
+        self.notify(supervisor::Command::Respawn { pid });
+    }
+
+    /// Kill a backend that never became ready and tell the frontend where to look
+    pub(crate) fn fail_startup(&self, processes: &mut ProcessRegistry, pid: u32, reason: String) {
+        // Ignore the outcome if the process was stopped or replaced meanwhile
+        if !processes.contains(pid) {
+            return;
+        }
+        log::error!("❌ Backend process {} failed to start: {}", pid, reason);
+        self.note(
+            log::Level::Warn,
+            &format!("Backend process {} killed: {}", pid, reason),
+        );
+
+        // Untrack first so the exit isn't mistaken for a crash and auto-restarted
+        if let Some(child) = processes.take(pid) {
+            let deadline = Instant::now() + self.shutdown_timeouts.deadline;
+            if let Err(e) = tauri::async_runtime::block_on(self.terminate(child, deadline)) {
+                log::error!("Failed to stop backend process {}: {:#}", pid, e);
+            }
+        }
+        self.transition(BackendState::Failed, Some(pid), Some(reason.clone()));
+
+        let log_path = self.stderr_log_path();
+        let log_tail = log_file::tail(&log_path, STARTUP_FAILED_TAIL_LINES).unwrap_or_else(|e| {
+            log::warn!("Failed to read {:?}: {}", log_path, e);
+            Vec::new()
+        });
+        let payload = StartupFailedPayload {
+            pid,
+            reason,
+            log_path: log_path.to_string_lossy().into_owned(),
+            log_tail,
+        };
+        if let Err(e) = self.app.emit("backend://startup-failed", payload) {
+            log::error!("Failed to emit backend://startup-failed: {}", e);
+        }
+    }
+
+    /// Wait until `pid` is ready according to `self.readiness`, probing
+    /// `health_url` for `Readiness::Http`
+    fn wait_until_ready(
+        &self,
+        pid: u32,
+        exited: &watch::Receiver<bool>,
+        health_url: String,
+        deadline: Instant,
+    ) -> Result<()> {
+        match &self.readiness {
+            Readiness::Http => {
+                self.wait_for_output(pid, exited, deadline, "startup banner", || {
+                    lock_or_recover(&self.bound_address)
+                        .as_ref()
+                        .is_some_and(|a| a.pid == pid)
+                })?;
+                self.wait_for_ready_at(
+                    health_url,
+                    deadline.saturating_duration_since(Instant::now()),
+                )
+            }
+            Readiness::LogMarker(marker) => self.wait_for_output(
+                pid,
+                exited,
+                deadline,
+                &format!("ready marker {:?}", marker),
+                || *lock_or_recover(&self.marker_seen) == Some(pid),
+            ),
+        }
+    }
+
+    /// Poll `seen` until it reports that `pid` printed `what`
+    fn wait_for_output(
+        &self,
+        pid: u32,
+        exited: &watch::Receiver<bool>,
+        deadline: Instant,
+        what: &str,
+        seen: impl Fn() -> bool,
+    ) -> Result<()> {
+        loop {
+            if seen() {
+                return Ok(());
+            }
+            // Closed once its output ended, which may come before the exit itself
+            if *exited.borrow() || exited.has_changed().is_err() {
+                return Err(anyhow!("Backend process {} exited during startup", pid));
+            }
+            if Instant::now() >= deadline {
+                return Err(anyhow!(
+                    "Backend stuck in startup: no {} after {:?}",
+                    what,
+                    self.startup_timeout
+                ));
+            }
+            std::thread::sleep(BANNER_POLL_INTERVAL);
+        }
+    }
+
+    fn record_bound_address(&self, pid: u32, host: String, port: u16) {
+        log::info!("Backend process {} listening on {}:{}", pid, host, port);
+        *lock_or_recover(&self.bound_address) = Some(BoundAddress { pid, host, port });
+    }
+
+    fn is_tracked(&self, pid: u32) -> bool {
+        self.tracked().contains(pid)
+    }
+
+    /// The tracked processes as the supervisor last published them
+    fn tracked(&self) -> ProcessSnapshot {
+        self.supervisor
+            .get()
+            .map(Supervisor::processes)
+            .unwrap_or_default()
+    }
+
+    /// Hand an event from a watching thread to the supervisor
+    fn notify(&self, event: supervisor::Command) {
+        if let Some(supervisor) = self.supervisor.get() {
+            supervisor.notify(event);
+        }
+    }
+
+    /// Current lifecycle state together with the tracked PID, if any
+    pub fn status(&self) -> BackendStatus {
+        // Read each field separately so no two locks are held at once
+        let tracked = self.tracked();
+        let pid = tracked.primary_pid();
+        let state = match *lock_or_recover(&self.state) {
+            // The process list is the source of truth for whether anything is alive
+            BackendState::Running if pid.is_none() => BackendState::Stopped,
+            state => state,
+        };
+        let services = tracked.list();
+        let last_error = lock_or_recover(&self.last_error).clone();
+        let last_exit = lock_or_recover(&self.last_exit).clone();
+        let address = lock_or_recover(&self.bound_address)
+            .as_ref()
+            .filter(|a| Some(a.pid) == pid)
+            .map(|a| format!("{}:{}", a.host, a.port));
+        BackendStatus {
+            state,
+            pid,
+            last_error,
+            last_exit,
+            address,
+            log_warning: self.log_dir_error.clone(),
+            services,
+        }
+    }
+
+    /// How long the serving backend has been up; `None` while none is tracked.
+    /// Restarts begin a new count.
+    pub fn backend_uptime(&self) -> Option<Duration> {
+        let pid = self.tracked().primary_pid();
+        (*lock_or_recover(&self.last_spawn))
+            .filter(|s| Some(s.pid) == pid)
+            .map(|s| s.at.elapsed())
+    }
+
+    /// Where the backend was resolved to and where its logs are written
+    pub fn paths(&self) -> BackendPaths {
+        BackendPaths {
+            backend_path: self.backend_path.display().to_string(),
+            log_dir: self.log_dir.display().to_string(),
+        }
+    }
+
+    /// Uptime and (re)start counters of this app session
+    pub fn stats(&self) -> BackendStats {
+        let pid = self.tracked().primary_pid();
+        let spawn = (*lock_or_recover(&self.last_spawn)).filter(|s| Some(s.pid) == pid);
+        let start_count = self.start_count.load(Ordering::SeqCst);
+        let last_exit_code = lock_or_recover(&self.last_exit)
+            .as_ref()
+            .and_then(|e| e.code);
+        BackendStats {
+            pid,
+            uptime_secs: spawn.map(|s| s.at.elapsed().as_secs()),
+            started_at: spawn.map(|s| s.timestamp),
+            start_count,
+            restart_count: start_count.saturating_sub(1),
+            last_exit_code,
+        }
+    }
+
+    /// Keep the latest stats on disk for post-mortem inspection
+    fn write_stats(&self) {
+        let path = self.log_dir.join(STATS_FILE);
+        let written = serde_json::to_vec_pretty(&self.stats())
+            .map_err(std::io::Error::from)
+            .and_then(|json| fs::write(&path, json));
+        if let Err(e) = written {
+            log::warn!("Failed to write {:?}: {}", path, e);
+        }
+    }
+
+    fn set_state(&self, state: BackendState) {
+        self.transition(state, None, None);
+    }
+
+    fn set_failed(&self, reason: String) {
+        self.transition(BackendState::Failed, None, Some(reason));
+    }
+
+    /// Update the state and notify the webview when it actually changed. The
+    /// state of the process `pid` in the registry is the supervisor's to set.
+    fn transition(&self, state: BackendState, pid: Option<u32>, reason: Option<String>) {
+        {
+            let mut current = lock_or_recover(&self.state);
+            if *current == state && reason.is_none() {
+                return;
+            }
+            log::info!("Backend state: {:?} -> {:?}", *current, state);
+            *current = state;
+        }
+        if let Some(supervisor) = self.supervisor.get() {
+            supervisor.publish(state);
+        }
+        *lock_or_recover(&self.last_error) = reason.clone();
+
+        let event = match state {
+            BackendState::Starting => "backend://starting",
+            BackendState::Running => "backend://ready",
+            BackendState::Stopped => "backend://stopped",
+            BackendState::Failed => "backend://failed",
+            BackendState::Installing | BackendState::Stopping => return,
+        };
+        let payload = LifecyclePayload {
+            pid,
+            timestamp: unix_millis(),
+            reason,
+        };
+        if let Err(e) = self.app.emit(event, payload) {
+            log::error!("Failed to emit {}: {}", event, e);
+        }
+    }
+
+    /// Restart the backend, streaming the new process into the same log file
+    pub fn restart_backend(&self, processes: &mut ProcessRegistry) -> Result<(), BackendError> {
+        log::info!("Restarting backend...");
+        let mut names = processes.names();
+        // A stopped API server is started again, and always comes first
+        names.retain(|name| name != BACKEND_PROCESS_NAME);
+        names.insert(0, BACKEND_PROCESS_NAME.to_string());
+
+        let mut first_error = None;
+        for name in names {
+            if let Err(e) = self.restart_one(processes, &name) {
+                log::error!("Failed to restart {}: {}", name, e.report());
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    /// Restart the process registered as `name` and leave the others alone
+    pub fn restart_process(
+        &self,
+        processes: &mut ProcessRegistry,
+        name: &str,
+    ) -> Result<(), BackendError> {
+        self.restart_one(processes, name)
+    }
+
+    /// Stop `name` and spawn it again with the module, arguments and dependency it
+    /// was started with. The API server also goes through dependency installation.
+    fn restart_one(&self, processes: &mut ProcessRegistry, name: &str) -> Result<(), BackendError> {
+        if name == BACKEND_PROCESS_NAME {
+            if processes.contains_name(name) {
+                self.stop_process(processes, name)?;
+                // Give the OS a moment to release the port before respawning
+                std::thread::sleep(RESTART_SETTLE_DELAY);
+            }
+            let extra_args = lock_or_recover(&self.extra_args).clone();
+            return self.start_with_args(processes, &extra_args);
+        }
+
+        let process = processes
+            .get(name)
+            .ok_or_else(|| BackendError::UnknownProcess(name.to_string()))?;
+        let (module, args, depends_on) = (
+            process.module.clone(),
+            process.args.clone(),
+            process.depends_on.clone(),
+        );
+        self.stop_process(processes, name)?;
+        processes.record_restart(name);
+        match depends_on {
+            // E.g. the API server restarted just before: queued again, so the
+            // supervisor can see it become ready before this one is launched
+            Some(dependency) if processes.state_of(&dependency) != Some(BackendState::Running) => {
+                let supervisor = self.supervisor()?.clone();
+                let name = name.to_string();
+                tauri::async_runtime::spawn(async move {
+                    let started = supervisor
+                        .start_process(name.clone(), module, args, Some(dependency), None)
+                        .await;
+                    if let Err(e) = started {
+                        log::error!("Failed to restart {}: {}", name, e.report());
+                    }
+                });
+            }
+            depends_on => {
+                self.start_process(processes, name, &module, &args, depends_on.as_deref())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the process registered as `name` and return its PID. Its exit is
+    /// expected, so the restart policy leaves it alone.
+    pub fn stop_process(
+        &self,
+        processes: &mut ProcessRegistry,
+        name: &str,
+    ) -> Result<u32, BackendError> {
+        let process = processes
+            .take_named(name)
+            .ok_or_else(|| BackendError::UnknownProcess(name.to_string()))?;
+        let pid = process.pid();
+        let primary = name == BACKEND_PROCESS_NAME;
+        if primary {
+            self.set_state(BackendState::Stopping);
+        }
+
+        let deadline = Instant::now() + self.shutdown_timeouts.deadline;
+        let result = tauri::async_runtime::block_on(self.terminate(process, deadline));
+        if primary {
+            self.set_state(BackendState::Stopped);
+        }
+        result.map_err(|e| BackendError::StopFailed {
+            failed: 1,
+            total: 1,
+            details: format!("{}: {:#}", pid, e),
+        })?;
+        self.note(log::Level::Info, &format!("Stopped {} (pid {})", name, pid));
+        Ok(pid)
+    }
+
+    /// State, PID, uptime and restart count of the process registered as `name`
+    pub fn process_status(&self, name: &str) -> Result<ProcessInfo, BackendError> {
+        self.tracked()
+            .info(name)
+            .ok_or_else(|| BackendError::UnknownProcess(name.to_string()))
+    }
+
+    /// Replace the backend without a gap: start a second instance on another port,
+    /// switch the frontend over once it is healthy, then stop the old one. Returns
+    /// the new API URL. When the new instance doesn't come up, the old one is kept.
+    pub fn reload_backend(&self, processes: &mut ProcessRegistry) -> Result<String, BackendError> {
+        let Some(old_pid) = processes.primary_pid() else {
+            log::info!("No backend to reload, starting one");
+            let extra_args = lock_or_recover(&self.extra_args).clone();
+            self.start_with_args(processes, &extra_args)?;
+            return Ok(self.api_url());
+        };
+
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .and_then(|l| l.local_addr())
+            .map(|addr| addr.port())
+            .context("No free port for the reloaded backend")?;
+        let slot = 1 - self.log_slot.load(Ordering::SeqCst);
+        let extra_args = lock_or_recover(&self.extra_args).clone();
+        let pid = self.launch(
+            processes,
+            RELOAD_PROCESS_NAME,
+            &self.main_module,
+            &extra_args,
+            Some(port),
+            self.backend_log_files(slot),
+        )?;
+        let exited = processes
+            .exit_watch(pid)
+            .unwrap_or_else(|| watch::channel(true).1);
+        self.note(
+            log::Level::Info,
+            &format!(
+                "Reloading backend: process {} starting on port {} next to {}",
+                pid, port, old_pid
+            ),
+        );
+
+        let deadline = Instant::now() + self.startup_timeout;
+        let url = Self::url_on(port, &self.health_check.path);
+        let ready = self.wait_until_ready(pid, &exited, url, deadline);
+        if let Err(e) = ready {
+            if let Some(child) = processes.take(pid) {
+                let deadline = Instant::now() + self.shutdown_timeouts.deadline;
+                if let Err(e) = tauri::async_runtime::block_on(self.terminate(child, deadline)) {
+                    log::error!("Failed to stop reload candidate {}: {:#}", pid, e);
+                }
+            }
+            self.note(
+                log::Level::Warn,
+                &format!("Reload aborted, process {} keeps serving: {:#}", old_pid, e),
+            );
+            return Err(BackendError::ReloadFailed(format!("{e:#}")));
+        }
+
+        // Switch over: the candidate becomes the serving backend
+        let old = processes.take(old_pid);
+        if *exited.borrow() || !processes.promote(pid, BACKEND_PROCESS_NAME) {
+            // The candidate died right after it became ready
+            let kept = old.map(|old| processes.insert(old));
+            let candidate = processes.take(pid);
+            let deadline = Instant::now() + self.shutdown_timeouts.deadline;
+            if let Some(Err(old)) = kept {
+                if let Err(e) = tauri::async_runtime::block_on(self.terminate(*old, deadline)) {
+                    log::error!("Failed to stop previous backend {}: {:#}", old_pid, e);
+                }
+            }
+            if let Some(child) = candidate {
+                if let Err(e) = tauri::async_runtime::block_on(self.terminate(child, deadline)) {
+                    log::error!("Failed to stop reload candidate {}: {:#}", pid, e);
+                }
+            }
+            return Err(BackendError::ReloadFailed(format!(
+                "the backend changed while process {} was starting",
+                pid
+            )));
+        }
+        processes.record_restart(BACKEND_PROCESS_NAME);
+        processes.set_state(pid, BackendState::Running);
+        self.port.store(port, Ordering::SeqCst);
+        self.log_slot.store(slot, Ordering::SeqCst);
+        self.write_pid_file(pid);
+        *lock_or_recover(&self.last_spawn) = Some(SpawnRecord {
+            pid,
+            at: Instant::now(),
+            timestamp: unix_millis(),
+        });
+        self.start_count.fetch_add(1, Ordering::SeqCst);
+        self.write_stats();
+
+        let url = self.api_url();
+        let payload = UrlChangedPayload {
+            pid,
+            url: url.clone(),
+        };
+        if let Err(e) = self.app.emit("backend://url-changed", payload) {
+            log::error!("Failed to emit backend://url-changed: {}", e);
+        }
+        self.transition(BackendState::Running, Some(pid), None);
+        self.spawn_health_watch(pid);
+
+        if let Some(old) = old {
+            let deadline = Instant::now() + self.shutdown_timeouts.deadline;
+            if let Err(e) = tauri::async_runtime::block_on(self.terminate(old, deadline)) {
+                log::error!("Failed to stop previous backend {}: {:#}", old_pid, e);
+            }
+        }
+        self.note(
+            log::Level::Info,
+            &format!(
+                "Backend reloaded: process {} serves {}, process {} stopped",
+                pid, url, old_pid
+            ),
+        );
+        Ok(url)
+    }
+
+    /// Stop all backend processes, blocking for at most the time a full shutdown
+    /// escalation can take. Must not be called from an async context.
+    ///
+    /// The bound holds even when a kill blocks inside the shutdown, which the
+    /// runtime's timeout can't interrupt: the shutdown runs on its own thread, and
+    /// is abandoned if that thread doesn't finish in time.
+    pub fn stop_all(&self) -> Result<Vec<u32>, BackendError> {
+        let budget = self.shutdown_timeouts.deadline + 2 * STOP_BUDGET_SLACK;
+        let (tx, rx) = std::sync::mpsc::channel();
+        let app = self.app.clone();
+        let spawned = std::thread::Builder::new()
+            .name("backend-stop".into())
+            .spawn(move || {
+                if let Some(manager) = app.try_state::<Self>() {
+                    let _ = tx.send(tauri::async_runtime::block_on(manager.shutdown_bounded()));
+                }
+            });
+        if let Err(e) = spawned {
+            log::warn!("Failed to start the stop thread, stopping inline: {}", e);
+            return self.stop_inline();
+        }
+        match rx.recv_timeout(budget) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                // The stuck shutdown keeps running, but nobody waits for it
+                log::error!(
+                    "❌ Backend cleanup abandoned after {:?}, backend processes may be orphaned",
+                    budget
+                );
+                Err(BackendError::StopTimedOut(budget))
+            }
+            // Not the managed instance, e.g. while it is being dropped
+            Err(RecvTimeoutError::Disconnected) => self.stop_inline(),
+        }
+    }
+
+    /// `stop_all` on the calling thread, bounded only by the runtime's timeout
+    fn stop_inline(&self) -> Result<Vec<u32>, BackendError> {
+        tauri::async_runtime::block_on(self.shutdown_bounded())
+    }
+
+    /// Kill every backend process and its descendants right away, without the
+    /// HTTP request or SIGINT and their grace periods. Must not be called from an
+    /// async context.
+    ///
+    /// Runs outside the supervisor queue, so it also reaches a backend a stuck
+    /// command is waiting on. The supervisor untracks the killed processes as
+    /// their exits come in, and doesn't restart them.
+    pub fn force_stop(&self) -> Result<(), BackendError> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let pids = self.tracked().pids();
+        if pids.is_empty() {
+            log::info!("Force stop: no backend process running");
+            return Ok(());
+        }
+        self.set_state(BackendState::Stopping);
+
+        let total = pids.len();
+        let mut failures = Vec::new();
+        for pid in pids {
+            if let Err(e) = tauri::async_runtime::block_on(self.kill_now(pid)) {
+                failures.push(format!("{}: {:#}", pid, e));
+            }
+        }
+        self.set_state(BackendState::Stopped);
+
+        if !failures.is_empty() {
+            return Err(BackendError::StopFailed {
+                failed: failures.len(),
+                total,
+                details: failures.join("; "),
+            });
+        }
+        self.remove_pid_file();
+        Ok(())
+    }
+
+    /// Forced kill of `pid` and everything it spawned, then a check that the
+    /// tree is gone
+    async fn kill_now(&self, pid: u32) -> Result<()> {
+        let label = self.process_label(pid);
+        // Taken before the parent dies, as in kill_descendants
+        let tree = process_tree::descendants(pid);
+        log::warn!("Force-stopping {} and descendants {:?}", label, tree);
+        self.force_kill_pids(&tree);
+        if self.force_kill_pids(&[pid]).is_empty() && process_tree::process_alive(pid) {
+            return Err(anyhow!("failed to kill {}", label));
+        }
+        if !process_tree::wait_for_process_exit(pid, FORCE_KILL_VERIFY_TIMEOUT).await {
+            log::warn!(
+                "No exit reported for {} after killing it; it may linger as a zombie",
+                label
+            );
+        }
+
+        if !process_tree::wait_for_tree_exit(pid, &tree, FORCE_KILL_VERIFY_TIMEOUT).await {
+            let survived = process_tree::surviving(pid, &tree);
+            return Err(anyhow!(
+                "descendants {:?} of {} survived a forced kill and may be orphaned",
+                survived,
+                pid
+            ));
+        }
+        self.reap_descendants(&tree);
+        log::info!("{} force-stopped", label);
+        Ok(())
+    }
+
+    /// `shutdown` with a hard upper bound, for callers that must go on regardless
+    pub async fn shutdown_bounded(&self) -> Result<Vec<u32>, BackendError> {
+        // shutdown_processes() enforces the deadline itself; this only guards
+        // against a stuck kill and a queue that doesn't get to it
+        let budget = self.shutdown_timeouts.deadline + STOP_BUDGET_SLACK;
+        tokio::time::timeout(budget, self.shutdown())
+            .await
+            .map_err(|_| BackendError::StopTimedOut(budget))?
+    }
+
+    /// Stop all backend processes and return the PIDs that were terminated; waits
+    /// by sleeping on the runtime, never by blocking the calling thread.
+    ///
+    /// Goes ahead of queued commands, but waits for the one the supervisor is
+    /// running, e.g. a start that is about to register its process.
+    pub async fn shutdown(&self) -> Result<Vec<u32>, BackendError> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        match self.supervisor.get() {
+            Some(supervisor) => supervisor.shutdown().await,
+            // Nothing was ever started without one
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// The supervisor's side of `stop_all` and `shutdown`, on its blocking thread
+    pub(crate) fn stop_tracked(
+        &self,
+        processes: &mut ProcessRegistry,
+    ) -> Result<Vec<u32>, BackendError> {
+        self.shutting_down.store(true, Ordering::SeqCst);
+        let budget = self.shutdown_timeouts.deadline + STOP_BUDGET_SLACK;
+        tauri::async_runtime::block_on(async {
+            tokio::time::timeout(budget, self.shutdown_processes(processes)).await
+        })
+        .map_err(|_| BackendError::StopTimedOut(budget))?
+    }
+
+    /// Stop every process in `processes`, dependents first.
+    ///
+    /// Takes at most `ShutdownTimeouts::deadline` plus the time of the final kills.
+    async fn shutdown_processes(
+        &self,
+        processes: &mut ProcessRegistry,
+    ) -> Result<Vec<u32>, BackendError> {
+        let deadline = Instant::now() + self.shutdown_timeouts.deadline;
+        let processes = processes.drain();
+        if processes.is_empty() {
+            log::info!("Backend already stopped, nothing to clean up");
+            return Ok(Vec::new());
+        }
+        self.set_state(BackendState::Stopping);
+        let total = processes.len();
+        let mut stopped = Vec::with_capacity(total);
+        let mut failures = Vec::new();
+        for process in processes {
+            let pid = process.pid();
+            match self.terminate(process, deadline).await {
+                Ok(()) => stopped.push(pid),
+                Err(e) => failures.push(format!("{}: {:#}", pid, e)),
+            }
+        }
+        self.set_state(BackendState::Stopped);
+
+        if failures.is_empty() {
+            // Kept after a failed stop so the next launch can still clean up
+            self.remove_pid_file();
+            #[cfg(all(debug_assertions, target_os = "linux"))]
+            {
+                let zombies = process_tree::zombie_children();
+                if !zombies.is_empty() {
+                    log::error!("Unreaped child processes after shutdown: {:?}", zombies);
+                }
+            }
+            Ok(stopped)
+        } else {
+            Err(BackendError::StopFailed {
+                failed: failures.len(),
+                total,
+                details: failures.join("; "),
+            })
+        }
+    }
+
+    /// Stop a process, asking it over HTTP first and killing it with everything it
+    /// spawned if that doesn't work. Past `deadline` it is killed immediately.
+    async fn terminate(&self, process: ProcessHandle, deadline: Instant) -> Result<()> {
+        let pid = process.pid();
+        let mut exited = process.exited.clone();
+        log::info!("Terminating {}", process.label());
+
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let graceful = self.stop_gracefully(pid, process.port, &mut exited);
+        let descendants = match tokio::time::timeout(remaining, graceful).await {
+            Ok(Ok(true)) => return Ok(()),
+            Ok(Ok(false)) => Ok(()),
+            Ok(Err(e)) => Err(e),
+            Err(_) => {
+                log::warn!(
+                    "Shutdown deadline passed, killing process {} immediately; its descendants may be orphaned",
+                    pid
+                );
+                Err(anyhow!(
+                    "shutdown deadline passed, descendants of {} may be orphaned",
+                    pid
+                ))
+            }
+        };
+
+        // Use CommandChild's kill method
+        let label = process.label();
+        process
+            .child
+            .kill()
+            .with_context(|| format!("failed to kill {}", label))?;
+        log::info!("{} force-killed", label);
+        // The plugin sends Terminated only after waiting on the child, so once it
+        // arrived the process is reaped rather than left behind as a zombie
+        if !Self::wait_for_exit(&mut exited, FORCE_KILL_VERIFY_TIMEOUT).await {
+            log::warn!(
+                "No exit reported for {} after killing it; it may linger as a zombie",
+                label
+            );
+        }
+        descendants
+    }
+
+    /// Escalate from the HTTP shutdown request through signals to killing the
+    /// descendants of `pid`. Returns `true` once `pid` exited on its own, `false`
+    /// when it still has to be killed.
+    async fn stop_gracefully(
+        &self,
+        pid: u32,
+        port: Option<u16>,
+        exited: &mut watch::Receiver<bool>,
+    ) -> Result<bool> {
+        let graceful = self.shutdown_timeouts.graceful;
+        let port = port.unwrap_or_else(|| self.port());
+        self.request_quiesce(pid, port).await;
+        if self.request_shutdown(port).await {
+            if Self::wait_for_exit(exited, graceful).await {
+                log::info!("Process {} exited after HTTP shutdown request", pid);
+                return Ok(true);
+            }
+            log::warn!(
+                "Process {} still running {:?} after HTTP shutdown request, sending signals",
+                pid,
+                graceful
+            );
+        }
+
+        // Let the direct child run its own cleanup before its descendants are touched
+        self.signal_child_gracefully(pid);
+        if Self::wait_for_exit(exited, graceful).await {
+            log::info!("Process {} exited after graceful signal", pid);
+            return Ok(true);
+        }
+        log::warn!(
+            "Process {} still running {:?} after graceful signal, killing process tree",
+            pid,
+            graceful
+        );
+
+        // Attempt to terminate any descendants spawned under this process BEFORE killing the parent
+        self.kill_descendants_best_effort(pid).await?;
+        Ok(false)
+    }
+
+    /// Stop a backend recorded in the PID file by a previous run of the app, if it
+    /// is still running and really is our backend
+    fn reap_orphan(&self) {
+        let record = match fs::read_to_string(&self.pid_file) {
+            Ok(contents) => match serde_json::from_str::<PidRecord>(&contents) {
+                Ok(record) => record,
+                Err(e) => {
+                    log::warn!("Ignoring unreadable PID file {:?}: {}", self.pid_file, e);
+                    self.remove_pid_file();
+                    return;
+                }
+            },
+            Err(_) => return,
+        };
+        let pid = record.pid;
+        if self.is_tracked(pid) {
+            return;
+        }
+
+        match process_tree::command_line(pid) {
+            Some(current)
+                if current == record.command_line && current.contains(&self.main_module) =>
+            {
+                log::warn!(
+                    "Backend process {} from a previous run is still alive, stopping it",
+                    pid
+                );
+                match tauri::async_runtime::block_on(self.terminate_orphan(pid)) {
+                    Ok(()) => self.note(
+                        log::Level::Info,
+                        &format!("Stopped orphaned backend process {}", pid),
+                    ),
+                    Err(e) => {
+                        log::error!("Failed to stop orphaned backend {}: {:#}", pid, e);
+                        return;
+                    }
+                }
+            }
+            Some(current) => log::info!(
+                "PID {} from the PID file now belongs to {:?}, leaving it alone",
+                pid,
+                current
+            ),
+            None => log::info!("Backend process {} from the PID file is gone", pid),
+        }
+        self.remove_pid_file();
+    }
+
+    /// Make sure nothing listens on `port`. A leftover backend of ours is stopped;
+    /// any other process is reported by PID and command line.
+    fn free_port(&self, port: u16) -> Result<(), BackendError> {
+        if TcpListener::bind((Ipv4Addr::LOCALHOST, port)).is_ok() {
+            return Ok(());
+        }
+
+        let Some(pid) = process_tree::port_owner(port) else {
+            return Err(BackendError::PortInUse {
+                port,
+                owner: "a process that could not be identified".to_string(),
+            });
+        };
+        let command_line = process_tree::command_line(pid).unwrap_or_default();
+        if self.is_tracked(pid) || !command_line.contains(&self.main_module) {
+            return Err(BackendError::PortInUse {
+                port,
+                owner: format!("process {} ({})", pid, command_line),
+            });
+        }
+
+        log::warn!(
+            "Port {} is held by a stale backend process {}, stopping it",
+            port,
+            pid
+        );
+        tauri::async_runtime::block_on(self.terminate_orphan(pid))
+            .with_context(|| format!("Failed to stop stale backend {} on port {}", pid, port))?;
+        self.note(
+            log::Level::Warn,
+            &format!(
+                "Stopped stale backend process {} that held port {}",
+                pid, port
+            ),
+        );
+        Ok(())
+    }
+
+    /// Same escalation as `terminate`, for a process we have no handle or output of
+    async fn terminate_orphan(&self, pid: u32) -> Result<()> {
+        let graceful = self.shutdown_timeouts.graceful;
+
+        self.signal_child_gracefully(pid);
+        if process_tree::wait_for_process_exit(pid, graceful).await {
+            log::info!("Orphaned process {} exited after graceful signal", pid);
+            return Ok(());
+        }
+        self.force_kill_pid(pid).await
+    }
+
+    /// Kill `pid` and its descendants without asking first
+    async fn force_kill_pid(&self, pid: u32) -> Result<()> {
+        let descendants = self.kill_descendants_best_effort(pid).await;
+
+        self.force_kill_pids(&[pid]);
+
+        if !process_tree::wait_for_process_exit(pid, FORCE_KILL_VERIFY_TIMEOUT).await {
+            return Err(anyhow!("process {} survived a forced kill", pid));
+        }
+        descendants
+    }
+
+    fn write_pid_file(&self, pid: u32) {
+        let Some(command_line) = process_tree::command_line(pid) else {
+            log::warn!(
+                "Could not read the command line of {}, not writing PID file",
+                pid
+            );
+            return;
+        };
+        let record = PidRecord {
+            pid,
+            spawned_at: unix_millis(),
+            command_line,
+        };
+        let written = self
+            .pid_file
+            .parent()
+            .map_or(Ok(()), create_dir_all)
+            .and_then(|()| fs::write(&self.pid_file, serde_json::to_vec(&record)?));
+        if let Err(e) = written {
+            log::warn!("Failed to write PID file {:?}: {}", self.pid_file, e);
+        }
+    }
+
+    fn remove_pid_file(&self) {
+        if let Err(e) = fs::remove_file(&self.pid_file) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                log::warn!("Failed to remove PID file {:?}: {}", self.pid_file, e);
+            }
+        }
+    }
+
+    /// SIGTERM on unix; a `taskkill` without `/F` (WM_CLOSE) on Windows
+    fn signal_child_gracefully(&self, pid: u32) {
+        #[cfg(unix)]
+        self.signal_pids(libc::SIGTERM, &[pid], "SIGTERM");
+
+        #[cfg(windows)]
+        self.run_kill_utility("taskkill", &["/PID", &pid.to_string()], "graceful taskkill");
+    }
+
+    /// Ask the backend to stop accepting actions and settle in-flight exchange
+    /// calls. Best effort: the outcome is only logged and never holds up the stop
+    /// for longer than the quiesce timeout.
+    async fn request_quiesce(&self, pid: u32, port: u16) {
+        let url = Self::url_on(port, &self.quiesce_path);
+        let timeout = self.shutdown_timeouts.quiesce;
+        match self.http.post(&url).timeout(timeout).send().await {
+            Ok(response) if response.status().is_success() => {
+                log::info!("Backend process {} acknowledged quiesce request", pid)
+            }
+            Ok(response) => log::warn!(
+                "Backend process {} refused quiesce request: HTTP {}",
+                pid,
+                response.status()
+            ),
+            Err(e) if e.is_timeout() => log::warn!(
+                "Backend process {} did not acknowledge quiesce request within {:?}",
+                pid,
+                timeout
+            ),
+            Err(e) => log::warn!(
+                "Backend process {} unreachable for quiesce request: {}",
+                pid,
+                e
+            ),
+        }
+    }
+
+    /// Ask the backend to shut itself down. Returns whether it accepted the request.
+    async fn request_shutdown(&self, port: u16) -> bool {
+        let url = Self::url_on(port, &self.shutdown_path);
+        let request = self
+            .http
+            .post(&url)
+            .timeout(SHUTDOWN_REQUEST_TIMEOUT)
+            .send();
+        match request.await {
+            Ok(response) if response.status().is_success() => {
+                log::info!("Backend accepted shutdown request");
+                true
+            }
+            Ok(response) => {
+                log::info!(
+                    "Backend refused shutdown request: HTTP {}",
+                    response.status()
+                );
+                false
+            }
+            Err(e) => {
+                log::info!("Backend unreachable for shutdown request: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Wait until the output stream of a process reported its termination; a
+    /// stream that ended without one counts too
+    async fn wait_for_exit(exited: &mut watch::Receiver<bool>, timeout: Duration) -> bool {
+        tokio::time::timeout(timeout, exited.wait_for(|&exited| exited))
+            .await
+            .is_ok()
+    }
+
+    /// Extract the bound host and port from a startup banner line
+    fn parse_banner(banner: &Regex, line: &str) -> Option<(String, u16)> {
+        let captures = banner.captures(line)?;
+        let host = captures.name("host").map_or("127.0.0.1", |m| m.as_str());
+        let port = captures.name("port")?.as_str().parse().ok()?;
+        Some((host.to_string(), port))
+    }
+
+    /// Banner pattern from `VALUECELL_READY_BANNER`, falling back to uvicorn's
+    fn ready_banner_pattern() -> Regex {
+        let default = || Regex::new(DEFAULT_READY_BANNER).expect("default banner pattern is valid");
+        match std::env::var(READY_BANNER_ENV) {
+            Ok(pattern) => Regex::new(&pattern).unwrap_or_else(|e| {
+                log::warn!("Ignoring invalid {}: {}", READY_BANNER_ENV, e);
+                default()
+            }),
+            Err(_) => default(),
+        }
+    }
+
+    fn stdout_log_path(&self) -> PathBuf {
+        self.log_path(self.log_slot.load(Ordering::SeqCst), STDOUT_LOG_FILE)
+    }
+
+    /// Python logging and tracebacks end up here, so this is the log to point users at
+    fn stderr_log_path(&self) -> PathBuf {
+        self.log_path(self.log_slot.load(Ordering::SeqCst), STDERR_LOG_FILE)
+    }
+
+    /// Log files of the API server writing to `slot`
+    fn backend_log_files(&self, slot: usize) -> LogFiles {
+        LogFiles {
+            stdout: self.log_path(slot, STDOUT_LOG_FILE),
+            stderr: self.log_path(slot, STDERR_LOG_FILE),
+            json: self.log_path(slot, JSON_LOG_FILE),
+        }
+    }
+
+    /// `backend-<name>.*`, so pruning treats them like the API server's logs
+    fn process_log_files(&self, name: &str) -> LogFiles {
+        let file = |suffix: &str| self.log_dir.join(format!("backend-{}.{}", name, suffix));
+        LogFiles {
+            stdout: file("out.log"),
+            stderr: file("err.log"),
+            json: file("jsonl"),
+        }
+    }
+
+    /// `name` for slot 0, `backend.alt.*` for the slot used across a reload
+    fn log_path(&self, slot: usize, name: &str) -> PathBuf {
+        match slot {
+            0 => self.log_dir.join(name),
+            _ => self
+                .log_dir
+                .join(name.replacen("backend.", "backend.alt.", 1)),
+        }
+    }
+
+    /// Show the backend log directory in Finder/Explorer, creating it if needed
+    pub fn open_log_dir(&self) -> Result<(), BackendError> {
+        create_dir_all(&self.log_dir)
+            .with_context(|| format!("Failed to create {:?}", self.log_dir))?;
+        // The opener takes a string; a lossy one would open some other path
+        let path = self.log_dir.to_str().with_context(|| {
+            format!(
+                "Can't open {:?}: the path isn't valid Unicode",
+                self.log_dir
+            )
+        })?;
+        self.app
+            .opener()
+            .open_path(path, None::<&str>)
+            .with_context(|| format!("Failed to open {:?}", self.log_dir))?;
+        Ok(())
+    }
+
+    /// Last `lines` lines of the current stdout or stderr log
+    pub fn read_log(&self, stream: LogStream, lines: usize) -> Result<Vec<String>, BackendError> {
+        let path = match stream {
+            LogStream::Stdout => self.stdout_log_path(),
+            LogStream::Stderr => self.stderr_log_path(),
+        };
+        Ok(log_file::tail(&path, lines).with_context(|| format!("Failed to read {:?}", path))?)
+    }
+
+    fn open_log(&self, path: PathBuf) -> Option<RotatingFile> {
+        if self.log_dir_error.is_some() {
+            return None;
+        }
+        match RotatingFile::open(path.clone(), self.max_log_bytes, self.max_log_files) {
+            Ok(file) => Some(file.timestamped(self.timestamps)),
+            Err(err) => {
+                log::error!("Failed to open backend log file {:?}: {}", path, err);
+                None
+            }
+        }
+    }
+
+    fn stream_backend_logs(
+        &self,
+        rx: Receiver<CommandEvent>,
+        pid: u32,
+        logs: LogFiles,
+        exit_tx: watch::Sender<bool>,
+    ) {
+        // Opened before the reader thread starts. Output the backend writes in the
+        // meantime waits in `rx`, which the shell plugin fills only as fast as it
+        // is read, so even the first lines reach the files.
+        let stdout = self.open_log(logs.stdout);
+        let stderr = self.open_log(logs.stderr);
+        // A stream without its file is mirrored to the app log so nothing is lost
+        let (mirror_stdout, mirror_stderr) = (
+            self.debug || stdout.is_none(),
+            self.debug || stderr.is_none(),
+        );
+        // Stamped inside the record, so the file itself stays one JSON object per line
+        let json = self
+            .json_logs
+            .then(|| self.open_log(logs.json))
+            .flatten()
+            .map(|file| file.timestamped(false));
+        let log_levels = self.log_levels;
+        let banner = self.ready_banner.clone();
+        let mut marker = match &self.readiness {
+            Readiness::LogMarker(marker) => Some(marker.clone()),
+            Readiness::Http => None,
+        };
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            let started_at = Instant::now();
+            let forwarder = LogForwarder::spawn(app.clone(), pid);
+            let on_banner = |host: String, port: u16| {
+                if let Some(manager) = app.try_state::<Self>() {
+                    manager.record_bound_address(pid, host, port);
+                }
+            };
+            let exit = Self::stream_to_file(
+                rx,
+                stdout,
+                stderr,
+                json,
+                &banner,
+                on_banner,
+                |stream, line| {
+                    if let Some(manager) = app.try_state::<Self>() {
+                        lock_or_recover(&manager.last_output).insert(pid, Instant::now());
+                        if marker
+                            .as_deref()
+                            .is_some_and(|marker| line.contains(marker))
+                        {
+                            log::info!("Backend process {} printed its ready marker", pid);
+                            *lock_or_recover(&manager.marker_seen) = Some(pid);
+                            marker = None;
+                        }
+                    }
+                    let mirror = match stream {
+                        LogStream::Stdout => mirror_stdout,
+                        LogStream::Stderr => mirror_stderr,
+                    };
+                    let level = log_levels.then(|| log_events::parse_level(line)).flatten();
+                    if mirror || level.is_some_and(|level| level <= log::Level::Warn) {
+                        let level = level.unwrap_or(log::Level::Info);
+                        log::log!(level, "[backend {} {:?}] {}", pid, stream, line);
+                    }
+                    forwarder.send(stream, line)
+                },
+            );
+            // Flush the last batch before the supervisor looks at the exit
+            drop(forwarder);
+            let Some(exit) = exit else {
+                return;
+            };
+            // Wakes a stop waiting for this process, even while the supervisor is busy
+            exit_tx.send_replace(true);
+            drop(exit_tx);
+            // The manager is looked up lazily so the thread doesn't keep it alive
+            if let Some(manager) = app.try_state::<Self>() {
+                manager.notify(supervisor::Command::Exited {
+                    pid,
+                    exit,
+                    uptime: started_at.elapsed(),
+                });
+            }
+        });
+    }
+
+    /// Called by the supervisor once the output of `pid` has ended. Processes still
+    /// present in the process list were not stopped by us, so failed ones are
+    /// respawned; returns the backoff to wait before that.
+    pub(crate) fn handle_exit(
+        &self,
+        processes: &mut ProcessRegistry,
+        restart_attempts: &mut u32,
+        pid: u32,
+        exit: TerminatedPayload,
+        uptime: Duration,
+    ) -> Option<Duration> {
+        let label = match processes.name_of(pid) {
+            Some(name) => format!("{} (pid {})", name, pid),
+            None => format!("process {}", pid),
+        };
+        let taken = processes.take(pid);
+        let tracked = taken.is_some();
+        let worker = taken.is_some_and(|p| p.module != self.main_module);
+        if !worker {
+            *lock_or_recover(&self.last_exit) = Some(exit.clone());
+        }
+        lock_or_recover(&self.last_output).remove(&pid);
+        self.write_stats();
+
+        // Untracked: stopped by us, or a failed reload candidate the old backend replaced
+        let expected = self.shutting_down.load(Ordering::SeqCst) || !tracked;
+        let payload = ExitedPayload {
+            pid,
+            code: exit.code,
+            signal: exit.signal,
+            clean: exit.code == Some(0),
+            expected,
+        };
+        if let Err(e) = self.app.emit("backend://exited", payload) {
+            log::error!("Failed to emit backend://exited: {}", e);
+        }
+        // Workers are not covered by the restart policy
+        if worker {
+            self.note(
+                log::Level::Warn,
+                &format!(
+                    "Backend {} exited (code: {:?}, signal: {:?})",
+                    label, exit.code, exit.signal
+                ),
+            );
+            return None;
+        }
+        if expected {
+            log::info!(
+                "Backend {} stopped (code: {:?}, signal: {:?})",
+                label,
+                exit.code,
+                exit.signal
+            );
+            return None;
+        }
+
+        if exit.code != Some(0) {
+            log::error!(
+                "Backend {} died (code: {:?}, signal: {:?})",
+                label,
+                exit.code,
+                exit.signal
+            );
+        }
+
+        if uptime >= RESTART_RESET_AFTER {
+            *restart_attempts = 0;
+        }
+
+        let TerminatedPayload { code, signal } = exit;
+        let policy = *lock_or_recover(&self.restart_policy);
+        if code == Some(0) && policy != RestartPolicy::Always {
+            self.note(
+                log::Level::Info,
+                &format!("Backend process {} exited cleanly", pid),
+            );
+            self.transition(BackendState::Stopped, Some(pid), None);
+            return None;
+        }
+        if code != Some(0) {
+            let reason = format!(
+                "Backend exited unexpectedly (code: {:?}, signal: {:?})",
+                code, signal
+            );
+            self.transition(BackendState::Failed, Some(pid), Some(reason));
+        }
+
+        *restart_attempts += 1;
+        let attempt = *restart_attempts;
+        let limit = policy.retry_limit();
+        if let Some(limit) = limit.filter(|&limit| attempt > limit) {
+            self.note(log::Level::Error, &format!(
+                "Backend process {} exited (code: {:?}, signal: {:?}); not restarting after {} attempts (policy {})",
+                pid, code, signal, limit, policy
+            ));
+            log::error!(
+                "❌ Backend is down, auto-restart stopped after {} attempts (policy {})",
+                limit,
+                policy
+            );
+            let payload = CrashedPayload {
+                pid,
+                code,
+                signal,
+                attempts: limit,
+            };
+            if let Err(e) = self.app.emit("backend://crashed", payload) {
+                log::error!("Failed to emit backend://crashed: {}", e);
+            }
+            return None;
+        }
+
+        let delay = self.restart_backoff.delay_for(attempt);
+        let budget = limit.map_or_else(|| "unlimited".to_string(), |limit| limit.to_string());
+        self.note(log::Level::Warn, &format!(
+            "Backend process {} exited (code: {:?}, signal: {:?}); restarting in {:?} (attempt {}/{})",
+            pid, code, signal, delay, attempt, budget
+        ));
+        // Waited outside the queue; spawn_replacement skips the respawn if the app
+        // shut down, or a start replaced the backend, meanwhile
+        Some(delay)
+    }
+
+    pub fn maintenance_schedule(&self) -> MaintenanceSchedule {
+        *lock_or_recover(&self.maintenance)
+    }
+
+    /// Validate, persist and apply a new maintenance restart schedule
+    pub fn set_maintenance_schedule(
+        &self,
+        schedule: MaintenanceSchedule,
+    ) -> Result<(), BackendError> {
+        schedule.validate()?;
+        schedule.save(&self.maintenance_file)?;
+        *lock_or_recover(&self.maintenance) = schedule;
+        log::info!("Maintenance schedule set to {:?}", schedule);
+        Ok(())
+    }
+
+    pub fn launch_settings(&self) -> LaunchSettings {
+        lock_or_recover(&self.launch_settings).clone()
+    }
+
+    /// Validate, persist and apply new launch settings; they take effect on the
+    /// next launch, services also on the next `start_service`
+    pub fn set_launch_settings(&self, settings: LaunchSettings) -> Result<(), BackendError> {
+        for service in &settings.services {
+            Self::validate_process(&service.name, &service.module, &service.args)?;
+        }
+        settings.save(&self.settings_file)?;
+        log::info!("Launch settings set to {:?}", settings);
+        let uv_path_changed = self.launch_settings().uv_path != settings.uv_path;
+        *lock_or_recover(&self.launch_settings) = settings;
+        if uv_path_changed {
+            self.forget_uv();
+        }
+        Ok(())
+    }
+
+    /// Start the task that executes UI lifecycle requests in order
+    pub fn spawn_supervisor(&self) {
+        if self
+            .supervisor
+            .set(Supervisor::spawn(self.app.clone()))
+            .is_err()
+        {
+            log::warn!("Backend supervisor already running");
+        }
+    }
+
+    /// Queue for lifecycle requests; fails before `spawn_supervisor`
+    pub fn supervisor(&self) -> Result<&Supervisor, BackendError> {
+        self.supervisor
+            .get()
+            .ok_or(BackendError::ManagerUnavailable)
+    }
+
+    /// Watch the clock and perform the daily maintenance restart when it is due.
+    /// Schedule changes are picked up on the next tick.
+    pub fn spawn_maintenance_scheduler(&self) {
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            let mut next = None;
+            loop {
+                std::thread::sleep(MAINTENANCE_POLL_INTERVAL);
+                let Some(manager) = app.try_state::<Self>() else {
+                    return;
+                };
+                let schedule = manager.maintenance_schedule();
+                let now = Local::now();
+
+                let due = match next {
+                    Some((planned, at)) if planned == schedule => at,
+                    _ => {
+                        next = schedule.next_after(now).map(|at| (schedule, at));
+                        if let Some((_, at)) = next {
+                            log::info!("Next maintenance restart at {}", at);
+                        }
+                        continue;
+                    }
+                };
+                if now < due {
+                    continue;
+                }
+
+                if now - due > MAINTENANCE_LATE_LIMIT {
+                    manager.note(
+                        log::Level::Warn,
+                        &format!(
+                            "Maintenance restart due at {} was missed, waiting for the next one",
+                            due
+                        ),
+                    );
+                } else {
+                    manager.run_maintenance_restart();
+                }
+                next = schedule.next_after(now).map(|at| (schedule, at));
+            }
+        });
+    }
+
+    /// Watch for the machine waking from sleep and make sure the backend survived it.
+    ///
+    /// A suspended machine doesn't run this thread, so a wake shows up as a jump of
+    /// the wall clock far beyond the poll interval. That works on every platform
+    /// without hooking into the OS power notifications.
+    pub fn spawn_resume_watcher(&self) {
+        let app = self.app.clone();
+        std::thread::spawn(move || {
+            let mut last = SystemTime::now();
+            loop {
+                std::thread::sleep(RESUME_POLL_INTERVAL);
+                let now = SystemTime::now();
+                let gap = now.duration_since(last).unwrap_or_default();
+                last = now;
+                if gap < RESUME_POLL_INTERVAL + RESUME_MIN_GAP {
+                    continue;
+                }
+                let Some(manager) = app.try_state::<Self>() else {
+                    return;
+                };
+                manager.handle_resume(gap);
+            }
+        });
+    }
+
+    /// Probe the backend after a wake and restart it if it doesn't recover in time
+    fn handle_resume(&self, slept: Duration) {
+        self.note(
+            log::Level::Info,
+            &format!("System resumed after about {}s of sleep", slept.as_secs()),
+        );
+        if self.tracked().primary_pid().is_none() || self.shutting_down.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let mut result = self.check_health();
+        if let Err(e) = &result {
+            log::warn!(
+                "Backend health check after wake failed ({}), retrying in {:?}",
+                e,
+                RESUME_GRACE_PERIOD
+            );
+            std::thread::sleep(RESUME_GRACE_PERIOD);
+            result = self.check_health();
+        }
+
+        let restarting = result.is_err();
+        let payload = ResumedPayload {
+            slept_secs: slept.as_secs(),
+            restarting,
+        };
+        if let Err(e) = self.app.emit("backend://resumed", payload) {
+            log::error!("Failed to emit backend://resumed: {}", e);
+        }
+        match result {
+            Ok(()) => log::info!("Backend is healthy after wake"),
+            Err(e) => {
+                self.note(
+                    log::Level::Warn,
+                    &format!("Backend unresponsive after sleep ({}), restarting it", e),
+                );
+                // Queued, so it doesn't race a start or stop from the UI
+                let restart = self.supervisor().map(|s| s.restart());
+                if let Err(e) = restart.and_then(tauri::async_runtime::block_on) {
+                    log::error!("Restart after sleep failed: {}", e.report());
+                }
+            }
+        }
+    }
+
+    /// Restart unless the backend is down or reports work that must not be cut off
+    fn run_maintenance_restart(&self) {
+        if self.tracked().primary_pid().is_none() {
+            log::info!("Maintenance restart skipped, the backend is not running");
+            return;
+        }
+        match self.backend_busy() {
+            Ok(false) => {}
+            Ok(true) => {
+                self.note(log::Level::Info, "Maintenance restart skipped, the backend reports active work; retrying tomorrow");
+                return;
+            }
+            Err(e) => {
+                self.note(log::Level::Warn, &format!(
+                    "Maintenance restart skipped, could not ask the backend for its status ({}); retrying tomorrow",
+                    e
+                ));
+                return;
+            }
+        }
+
+        self.note(log::Level::Info, "Starting scheduled maintenance restart");
+        let restart = self.supervisor().map(|s| s.restart());
+        match restart.and_then(tauri::async_runtime::block_on) {
+            Ok(()) => self.note(log::Level::Info, "Scheduled maintenance restart completed"),
+            Err(e) => self.note(
+                log::Level::Error,
+                &format!("Scheduled maintenance restart failed: {}", e.report()),
+            ),
+        }
+    }
+
+    /// Whether the health endpoint reports an operation in flight via `data.busy`
+    fn backend_busy(&self) -> Result<bool, String> {
+        self.health_flag("/data/busy")
+    }
+
+    /// Boolean at `pointer` in the health response; absent means `false`
+    fn health_flag(&self, pointer: &str) -> Result<bool, String> {
+        let url = self.health_url();
+        let request = async {
+            let response = self
+                .http
+                .get(&url)
+                .timeout(self.health_check.request_timeout)
+                .send()
+                .await
+                .map_err(|e| e.to_string())?;
+            if response.status() != StatusCode::OK {
+                return Err(format!("HTTP {}", response.status()));
+            }
+            response.bytes().await.map_err(|e| e.to_string())
+        };
+        let body = tauri::async_runtime::block_on(request)?;
+        let status: serde_json::Value = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+        Ok(status
+            .pointer(pointer)
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false))
+    }
+
+    /// Record user activity in the UI; `true` when the backend was stopped for
+    /// being idle and has to be woken with `wake_from_idle`
+    pub fn record_activity(&self) -> bool {
+        *lock_or_recover(&self.last_activity) = Instant::now();
+        self.idle_stopped.swap(false, Ordering::SeqCst)
+    }
+
+    /// Start a backend stopped for being idle again, so the next request finds
+    /// it running
+    pub fn wake_from_idle(&self, processes: &mut ProcessRegistry) -> Result<(), BackendError> {
+        self.note(
+            log::Level::Info,
+            "Activity after an idle shutdown, starting backend again",
+        );
+        if let Err(e) = self.app.emit("backend://waking", ()) {
+            log::error!("Failed to emit backend://waking: {}", e);
+        }
+        let extra_args = lock_or_recover(&self.extra_args).clone();
+        match self.start_with_args(processes, &extra_args) {
+            Err(BackendError::AlreadyRunning { .. }) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Stop the backend after `idle_timeout` without UI activity, unless it is
+    /// live trading or busy. It comes back with the next `activity_heartbeat`.
+    pub fn spawn_idle_watcher(&self) {
+        let Some(timeout) = self.idle_timeout else {
+            return;
+        };
+        let app = self.app.clone();
+        std::thread::spawn(move || loop {
+            std::thread::sleep(IDLE_POLL_INTERVAL);
+            let Some(manager) = app.try_state::<Self>() else {
+                return;
+            };
+            let idle_for = lock_or_recover(&manager.last_activity).elapsed();
+            if idle_for >= timeout && manager.has_process() {
+                manager.stop_if_idle(idle_for);
+            }
+        });
+    }
+
+    fn stop_if_idle(&self, idle_for: Duration) {
+        // Anything we can't confirm as safe keeps the backend running
+        let exempt = self
+            .health_flag("/data/live_trading")
+            .and_then(|live| Ok(live || self.backend_busy()?));
+        match exempt {
+            Ok(false) => {}
+            Ok(true) => {
+                log::info!("Backend idle but trading or busy, keeping it running");
+                return;
+            }
+            Err(e) => {
+                log::info!(
+                    "Backend idle but its status is unknown ({}), keeping it running",
+                    e
+                );
+                return;
+            }
+        }
+
+        self.note(
+            log::Level::Info,
+            &format!(
+                "No UI activity for {}s, stopping idle backend",
+                idle_for.as_secs()
+            ),
+        );
+        let stop = self.supervisor().map(|s| s.stop());
+        match stop.and_then(tauri::async_runtime::block_on) {
+            Ok(_) => {
+                self.idle_stopped.store(true, Ordering::SeqCst);
+                if let Err(e) = self.app.emit("backend://idle-stopped", ()) {
+                    log::error!("Failed to emit backend://idle-stopped: {}", e);
+                }
+            }
+            Err(e) => log::error!("Idle shutdown failed: {}", e.report()),
+        }
+    }
+
+    /// Whether a backend process is tracked, whatever its state
+    pub fn has_process(&self) -> bool {
+        !self.tracked().is_empty()
+    }
+
+    /// Kill the process trees last published by the supervisor, for when it
+    /// can't be asked anymore
+    fn kill_tracked(&self) {
+        for pid in self.tracked().pids() {
+            let mut tree = process_tree::descendants(pid);
+            tree.push(pid);
+            self.force_kill_pids(&tree);
+        }
+    }
+
+    pub fn restart_policy(&self) -> RestartPolicy {
+        *lock_or_recover(&self.restart_policy)
+    }
+
+    /// Switch the restart policy; the retry budget starts over
+    pub fn set_restart_policy(&self, policy: RestartPolicy) {
+        *lock_or_recover(&self.restart_policy) = policy;
+        self.notify(supervisor::Command::ResetRestarts);
+        log::info!("Restart policy set to {}", policy);
+    }
+
+    /// Log a manager message at `level` and record it next to the backend's own diagnostics
+    fn note(&self, level: log::Level, message: &str) {
+        log::log!(level, "{}", message);
+        if self.log_dir_error.is_some() {
+            return;
+        }
+
+        let log_path = self.stderr_log_path();
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .and_then(|mut file| {
+                if self.timestamps {
+                    write!(file, "{} ", log_file::timestamp())?;
+                }
+                writeln!(file, "[backend-manager] {}", message)
+            });
+        if let Err(err) = written {
+            log::error!(
+                "Failed to write to backend log file {:?}: {}",
+                log_path,
+                err
+            );
+        }
+    }
+
+    fn stream_to_file(
+        mut rx: Receiver<CommandEvent>,
+        mut stdout: Option<RotatingFile>,
+        mut stderr: Option<RotatingFile>,
+        mut json: Option<RotatingFile>,
+        banner: &Regex,
+        mut on_banner: impl FnMut(String, u16),
+        mut on_line: impl FnMut(LogStream, &str),
+    ) -> Option<TerminatedPayload> {
+        let mut banner_seen = false;
+        // When each file last failed, until a write works again
+        let (mut stdout_failed, mut stderr_failed, mut json_failed) = (None, None, None);
+        let mut handle_line = |stream: LogStream, text: &str| {
+            if !banner_seen {
+                if let Some((host, port)) = Self::parse_banner(banner, text) {
+                    banner_seen = true;
+                    on_banner(host, port);
+                }
+            }
+            on_line(stream, text);
+            let (file, failed) = match stream {
+                LogStream::Stdout => (&mut stdout, &mut stdout_failed),
+                LogStream::Stderr => (&mut stderr, &mut stderr_failed),
+            };
+            if let Some(file) = file.as_mut() {
+                Self::write_log_line(file, failed, text);
+            }
+            if let Some(file) = json.as_mut() {
+                let record = serde_json::json!({
+                    "ts": log_file::timestamp(),
+                    "stream": stream,
+                    "line": text,
+                });
+                Self::write_log_line(file, &mut json_failed, &record.to_string());
+            }
+        };
+
+        // Chunks aren't necessarily whole lines: the plugin also ends one at a
+        // `\r`, which splits `\r\n`, and in raw mode anywhere
+        let (mut stdout_lines, mut stderr_lines) = (LineBuffer::default(), LineBuffer::default());
+        let mut exit = None;
+        // Runs until the process is gone: the channel is bounded, so not reading it
+        // would fill the pipes and stall the backend
+        while let Some(event) = rx.blocking_recv() {
+            let (stream, lines) = match event {
+                CommandEvent::Stdout(chunk) => (LogStream::Stdout, stdout_lines.push(&chunk)),
+                CommandEvent::Stderr(chunk) => (LogStream::Stderr, stderr_lines.push(&chunk)),
+                // A failed read of one pipe, the other one and the exit still follow
+                CommandEvent::Error(err) => {
+                    log::error!("Backend process error: {}", err);
+                    continue;
+                }
+                CommandEvent::Terminated(payload) => {
+                    exit = Some(payload);
+                    break;
+                }
+                _ => continue,
+            };
+            for line in lines {
+                handle_line(stream, &line);
+            }
+        }
+
+        // A last line without a newline
+        for (stream, buffer) in [
+            (LogStream::Stdout, &mut stdout_lines),
+            (LogStream::Stderr, &mut stderr_lines),
+        ] {
+            if let Some(line) = buffer.finish() {
+                handle_line(stream, &line);
+            }
+        }
+        exit
+    }
+
+    /// Write `line` to a backend log file. A failure is logged once; until the
+    /// file works again, lines are dropped and it is reopened every
+    /// `LOG_REOPEN_INTERVAL`, which also recreates a deleted file.
+    fn write_log_line(file: &mut RotatingFile, failed: &mut Option<Instant>, line: &str) {
+        if let Some(last_attempt) = *failed {
+            if last_attempt.elapsed() < LOG_REOPEN_INTERVAL {
+                return;
+            }
+            *failed = Some(Instant::now());
+            if file.reopen().is_err() {
+                return;
+            }
+        }
+        match file.write_line(line) {
+            Ok(()) => {
+                if failed.take().is_some() {
+                    log::info!("Writing backend log {:?} again", file.path());
+                }
+            }
+            Err(err) => {
+                if failed.replace(Instant::now()).is_none() {
+                    log::error!(
+                        "Failed to write backend log {:?}, retrying every {:?}: {}",
+                        file.path(),
+                        LOG_REOPEN_INTERVAL,
+                        err
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl<R: Runtime> Drop for BackendManager<R> {
+    fn drop(&mut self) {
+        // The app state may already be torn down and the supervisor with it, so
+        // nothing is asked to stop; the trees last published are killed outright
+        self.kill_tracked();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::test::MockRuntime;
+
+    /// `value` behind a mutex that a panicking thread left poisoned
+    fn poisoned<T: Send>(value: T) -> Mutex<T> {
+        let mutex = Mutex::new(value);
+        std::thread::scope(|scope| {
+            let _ = scope
+                .spawn(|| {
+                    let _guard = mutex.lock().unwrap();
+                    panic!("poisoning the lock on purpose");
+                })
+                .join();
+        });
+        assert!(mutex.is_poisoned());
+        mutex
+    }
+
+    #[test]
+    fn lock_or_recover_keeps_the_value_of_a_poisoned_mutex() {
+        let mutex = poisoned(vec![1, 2]);
+        lock_or_recover(&mutex).push(3);
+        assert_eq!(*lock_or_recover(&mutex), [1, 2, 3]);
+    }
+
+    #[test]
+    fn shutdown_stops_a_process_after_a_command_panicked() {
+        let app = MockApp::new("panic");
+        let handle = app.handle();
+        let (tx, rx) = std::sync::mpsc::channel();
+        // Panics with the process registered, like a start failing halfway through
+        let command = supervisor::Command::Run(Box::new(move |processes| {
+            let _ = tx.send(MockApp::track(&handle, processes, "sleep", &["30"]));
+            panic!("failing a supervisor command on purpose");
+        }));
+        app.manager().notify(command);
+        let pid = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        assert_eq!(app.manager().backend_pids(), [pid]);
+
+        assert_eq!(app.manager().stop_all().unwrap(), [pid]);
+        assert!(!process_tree::process_alive(pid));
+        assert!(!app.manager().has_process());
+    }
+
+    #[test]
+    #[cfg(all(debug_assertions, target_os = "linux"))]
+    fn stopping_a_process_tree_leaves_no_zombie_children() {
+        let app = MockApp::new("zombies");
+        let handle = app.handle();
+        let (tx, rx) = std::sync::mpsc::channel();
+        // Ignored by sleep too, so the stop escalates all the way to killing the tree
+        let script = "trap '' TERM; sleep 30 & wait";
+        let command = supervisor::Command::Run(Box::new(move |processes| {
+            let _ = tx.send(MockApp::track(&handle, processes, "sh", &["-c", script]));
+        }));
+        app.manager().notify(command);
+        let pid = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while process_tree::descendants(pid).is_empty() {
+            assert!(Instant::now() < deadline, "sh never forked sleep");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let tree = process_tree::descendants(pid);
+
+        assert_eq!(app.manager().stop_all().unwrap(), [pid]);
+        let zombies = process_tree::zombie_children();
+        assert!(!zombies.contains(&pid), "{} left unreaped", pid);
+        assert!(process_tree::surviving(pid, &tree).is_empty());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn force_kill_leaves_nothing_of_a_cmd_tree() {
+        let app = MockApp::new("cmd-tree");
+        // cmd waiting on a child, like uv on python
+        let mut cmd = std::process::Command::new("cmd")
+            .args(["/C", "ping -n 30 127.0.0.1 > NUL"])
+            .spawn()
+            .unwrap();
+        let pid = cmd.id();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while process_tree::descendants(pid).is_empty() {
+            assert!(Instant::now() < deadline, "cmd never started ping");
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        let tree = process_tree::descendants(pid);
+
+        // What stop_all ends with for a tree that ignored every request
+        tauri::async_runtime::block_on(app.manager().force_kill_pid(pid)).unwrap();
+        cmd.wait().unwrap();
+        let alive: Vec<u32> = tree
+            .iter()
+            .copied()
+            .filter(|&descendant| process_tree::process_alive(descendant))
+            .collect();
+        assert!(alive.is_empty(), "descendants {:?} survived", alive);
+    }
+
+    /// Fresh directory under the temp dir, removed again when dropped
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(test: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("valuecell-{}-{}", test, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        /// A name with a space, CJK text and an apostrophe, the characters user
+        /// profile paths trip over
+        fn unusual(test: &str) -> Self {
+            Self::new(&format!("{} 市场分析 O'Brien", test))
+        }
+    }
+
+    impl std::ops::Deref for TempDir {
+        type Target = Path;
+
+        fn deref(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    type MockManager = BackendManager<MockRuntime>;
+
+    /// App on the mock runtime with a manager and its supervisor. The data and
+    /// log dirs are unique to the test and removed again when dropped.
+    struct MockApp {
+        app: tauri::App<MockRuntime>,
+        _backend: TempDir,
+    }
+
+    impl MockApp {
+        fn new(test: &str) -> Self {
+            // The override is only read while a manager is created
+            static CREATING: Mutex<()> = Mutex::new(());
+            let backend = TempDir::new(&format!("{}-backend", test));
+            fs::write(backend.join("pyproject.toml"), "[project]\n").unwrap();
+
+            let mut context = tauri::test::mock_context(tauri::test::noop_assets());
+            context.config_mut().identifier =
+                format!("valuecell.test.{}.{}", test, std::process::id());
+            let app = tauri::test::mock_builder()
+                .plugin(tauri_plugin_shell::init())
+                .build(context)
+                .unwrap();
+            let timeouts = ShutdownTimeouts {
+                quiesce: Duration::from_millis(200),
+                graceful: Duration::from_millis(500),
+                before_kill: Duration::from_millis(500),
+                deadline: Duration::from_secs(5),
+            };
+            let manager = {
+                let _creating = lock_or_recover(&CREATING);
+                std::env::set_var(BACKEND_DIR_ENV, &*backend);
+                MockManager::with_timeouts(app.handle().clone(), timeouts, Duration::from_secs(5))
+                    .unwrap()
+            };
+            app.manage(manager);
+            app.state::<MockManager>().spawn_supervisor();
+            Self {
+                app,
+                _backend: backend,
+            }
+        }
+
+        fn handle(&self) -> AppHandle<MockRuntime> {
+            self.app.handle().clone()
+        }
+
+        fn manager(&self) -> tauri::State<'_, MockManager> {
+            self.app.state()
+        }
+
+        /// Spawn `program` and register it under its own name the way `launch`
+        /// does, with its output streamed and its exit reported
+        fn track(
+            app: &AppHandle<MockRuntime>,
+            processes: &mut ProcessRegistry,
+            program: &str,
+            args: &[&str],
+        ) -> u32 {
+            let manager = app.state::<MockManager>();
+            let (rx, child) = app.shell().command(program).args(args).spawn().unwrap();
+            let pid = child.pid();
+            let (exit_tx, exited) = watch::channel(false);
+            manager.stream_backend_logs(rx, pid, manager.process_log_files(program), exit_tx);
+            // Nothing listens there, so the shutdown request fails right away
+            let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+                .and_then(|l| l.local_addr())
+                .unwrap()
+                .port();
+            let registered = processes.insert(ProcessHandle {
+                name: program.to_string(),
+                module: program.to_string(),
+                args: args.iter().map(|arg| arg.to_string()).collect(),
+                pid,
+                started_at: unix_millis(),
+                started: Instant::now(),
+                state: BackendState::Running,
+                depends_on: None,
+                port: Some(port),
+                child,
+                exited,
+            });
+            assert!(registered.is_ok(), "{} is already tracked", program);
+            pid
+        }
+    }
+
+    impl Drop for MockApp {
+        fn drop(&mut self) {
+            if let Ok(dir) = self.app.path().app_data_dir() {
+                let _ = fs::remove_dir_all(dir);
+            }
+        }
+    }
+
+    #[test]
+    fn override_dir_prefers_the_new_variable() {
+        let absolute = |path: &str| std::path::absolute(path).unwrap();
+        let both = |name: &str| match name {
+            BACKEND_DIR_ENV => Some("/opt/tradebyweex/backend".into()),
+            LEGACY_BACKEND_DIR_ENV => Some("/opt/valuecell/backend".into()),
+            _ => None,
+        };
+        assert_eq!(
+            <BackendManager>::override_dir(both),
+            Some((BACKEND_DIR_ENV, absolute("/opt/tradebyweex/backend")))
+        );
+
+        let legacy = |name: &str| match name {
+            BACKEND_DIR_ENV => Some("".into()),
+            LEGACY_BACKEND_DIR_ENV => Some("/opt/valuecell/backend".into()),
+            _ => None,
+        };
+        assert_eq!(
+            <BackendManager>::override_dir(legacy),
+            Some((LEGACY_BACKEND_DIR_ENV, absolute("/opt/valuecell/backend")))
+        );
+
+        assert_eq!(<BackendManager>::override_dir(|_| None), None);
+    }
+
+    #[test]
+    fn first_backend_dir_skips_missing_and_foreign_dirs() {
+        let root = TempDir::new("layout");
+        let resources = root.join("resources/backend");
+        let deb = root.join("lib/valuecell/resources/backend");
+        fs::create_dir_all(&resources).unwrap();
+        fs::write(resources.join("icon.png"), "").unwrap();
+        fs::create_dir_all(deb.join("valuecell")).unwrap();
+
+        let candidates = vec![
+            (BACKEND_DIR_ENV, root.join("missing")),
+            ("resource dir", resources.clone()),
+            ("executable-relative", deb.clone()),
+        ];
+        let found = <BackendManager>::first_backend_dir(candidates);
+        let none = <BackendManager>::first_backend_dir(vec![
+            (BACKEND_DIR_ENV, root.join("missing")),
+            ("AppImage", root.join("usr/lib/valuecell/backend")),
+        ]);
+
+        assert_eq!(found.unwrap(), ("executable-relative", deb));
+        match none {
+            Err(BackendError::BackendDirMissing { tried }) => assert_eq!(
+                tried,
+                [root.join("missing"), root.join("usr/lib/valuecell/backend")]
+            ),
+            other => panic!("expected BackendDirMissing, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validate_backend_dir_accepts_unusual_paths() {
+        let dir = TempDir::unusual("valid");
+        fs::write(dir.join("pyproject.toml"), "[project]\n").unwrap();
+        let result = <BackendManager>::validate_backend_dir(&dir);
+        assert!(result.is_ok(), "{:?}", result);
+    }
+
+    #[test]
+    fn validate_backend_dir_reports_the_exact_path() {
+        let dir = TempDir::unusual("invalid");
+        fs::write(dir.join("README 说明.md"), "").unwrap();
+        let result = <BackendManager>::validate_backend_dir(&dir);
+        match result {
+            Err(BackendError::BackendDirInvalid { path, found }) => {
+                assert_eq!(path, *dir);
+                assert_eq!(found, ["README 说明.md"]);
+            }
+            other => panic!("expected BackendDirInvalid, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn fit_path_keeps_unusual_characters() {
+        let dir = TempDir::unusual("fit");
+        assert_eq!(<BackendManager>::fit_path(&dir, 100).unwrap(), *dir);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn simplified_drops_only_the_verbatim_prefix() {
+        let verbatim = Path::new(r"\\?\C:\Users\O'Brien 市场分析\AppData\backend");
+        assert_eq!(
+            dunce::simplified(verbatim),
+            Path::new(r"C:\Users\O'Brien 市场分析\AppData\backend")
+        );
+    }
+
+    #[test]
+    fn arguments_with_spaces_cjk_and_apostrophes_pass() {
+        let args = [
+            "--data-dir".to_string(),
+            "/home/O'Brien/市场 分析/data".to_string(),
+            "--name=it's fine".to_string(),
+        ];
+        assert!(<BackendManager>::check_arguments(&args).is_ok());
+    }
+
+    #[test]
+    fn arguments_chaining_commands_are_refused() {
+        for arg in ["a; rm -rf ~", "x | y", "$(whoami)", "`id`", "a && b"] {
+            let args = ["--ok".to_string(), arg.to_string()];
+            match <BackendManager>::check_arguments(&args) {
+                Err(BackendError::InvalidArgument(refused)) => assert_eq!(refused, arg),
+                other => panic!("{:?} was not refused: {:?}", arg, other),
+            }
+        }
+    }
+
+    #[test]
+    fn first_output_line_reaches_the_log_file() {
+        let dir = TempDir::new("first-line");
+        let path = dir.join("backend.stdout.log");
+        let stdout = RotatingFile::open(path.clone(), 1024 * 1024, 2).unwrap();
+        let banner = Regex::new(DEFAULT_READY_BANNER).unwrap();
+
+        // Everything is queued before the reader starts, as when the backend
+        // prints before the log thread is scheduled
+        let (tx, rx) = tokio::sync::mpsc::channel(8);
+        let first = "INFO:     Uvicorn running on http://127.0.0.1:8123 (Press CTRL+C to quit)";
+        tx.try_send(CommandEvent::Stdout(format!("{}\n", first).into_bytes()))
+            .unwrap();
+        tx.try_send(CommandEvent::Terminated(TerminatedPayload {
+            code: Some(0),
+            signal: None,
+        }))
+        .unwrap();
+        drop(tx);
+
+        let mut bound = None;
+        let mut lines = Vec::new();
+        let exit = <BackendManager>::stream_to_file(
+            rx,
+            Some(stdout),
+            None,
+            None,
+            &banner,
+            |host, port| bound = Some((host, port)),
+            |stream, line| lines.push((stream, line.to_string())),
+        );
+        let written = fs::read_to_string(&path);
+
+        assert_eq!(exit.and_then(|exit| exit.code), Some(0));
+        assert_eq!(bound, Some(("127.0.0.1".to_string(), 8123)));
+        assert_eq!(lines, [(LogStream::Stdout, first.to_string())]);
+        assert_eq!(written.unwrap().lines().next(), Some(first));
     }
 }
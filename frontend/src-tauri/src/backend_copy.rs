@@ -0,0 +1,204 @@
+//! Writable copy of the bundled backend.
+//!
+//! The resource directory is read-only in signed macOS builds and AppImages, but
+//! uv sync creates `.venv` next to `pyproject.toml`. The backend therefore runs
+//! from a copy in the app data dir, brought up to date with the bundle before
+//! each start. A marker written last records which bundle the copy matches, so a
+//! copy that was interrupted halfway is noticed and finished instead of used.
+//! Only files that came from the bundle are ever removed from the copy.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Written into the copy once it matches the bundle
+const MARKER_FILE: &str = ".valuecell-copy";
+/// Every file the copy got from the bundle, one relative path per line
+const MANIFEST_FILE: &str = ".valuecell-files";
+/// Appended to a file name while it is being copied
+const PARTIAL_SUFFIX: &str = ".partial";
+/// Generated directories that are neither copied nor removed from the copy
+const SKIPPED_DIRS: &[&str] = &[
+    ".venv",
+    "__pycache__",
+    ".git",
+    ".mypy_cache",
+    ".pytest_cache",
+    ".ruff_cache",
+];
+/// Files between progress log lines
+const PROGRESS_STEP: usize = 500;
+
+/// What `sync` did to the copy
+pub struct CopyReport {
+    pub copied: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+struct Entry {
+    relative: PathBuf,
+    len: u64,
+    modified: SystemTime,
+}
+
+/// Make `target` an up-to-date copy of `source`: files that are missing or whose
+/// size or modification time differ are copied, files an earlier copy took from
+/// `source` that are no longer there are removed. Returns `None` without
+/// touching anything when the marker says `target` already matches.
+pub fn sync(source: &Path, target: &Path) -> io::Result<Option<CopyReport>> {
+    let mut entries = walk(source)?;
+    entries.sort_by(|a, b| a.relative.cmp(&b.relative));
+    let fingerprint = fingerprint(&entries);
+    let marker = target.join(MARKER_FILE);
+    match fs::read_to_string(&marker) {
+        Ok(copied) if copied == fingerprint => return Ok(None),
+        Ok(_) => log::info!("Bundled backend changed, updating the copy in {:?}", target),
+        Err(_) if target.join(MANIFEST_FILE).exists() => {
+            log::warn!("Backend copy in {:?} is incomplete, finishing it", target)
+        }
+        Err(_) => log::info!("Copying the bundled backend to {:?}", target),
+    }
+
+    fs::create_dir_all(target)?;
+    // Gone until the copy is complete again, so an interruption is noticed next time
+    remove_if_present(&marker)?;
+
+    let wanted: HashSet<PathBuf> = entries.iter().map(|e| e.relative.clone()).collect();
+    let previous = read_manifest(target)?;
+    // Lists what this run may write before it writes it, so files of an
+    // interrupted run are still known when they have to go
+    write_manifest(target, previous.union(&wanted))?;
+
+    let mut report = CopyReport {
+        copied: 0,
+        removed: 0,
+        unchanged: 0,
+    };
+    for (done, entry) in entries.iter().enumerate() {
+        let dest = target.join(&entry.relative);
+        if is_current(&dest, entry) {
+            report.unchanged += 1;
+        } else {
+            copy_file(&source.join(&entry.relative), &dest, entry.modified)?;
+            report.copied += 1;
+        }
+        if (done + 1) % PROGRESS_STEP == 0 {
+            log::info!("Backend copy: {}/{} files", done + 1, entries.len());
+        }
+    }
+
+    for stale in previous.difference(&wanted) {
+        let path = target.join(stale);
+        if remove_if_present(&path)? {
+            report.removed += 1;
+        }
+        remove_if_present(&partial_path(&path))?;
+    }
+
+    write_manifest(target, wanted.iter())?;
+    fs::write(&marker, fingerprint)?;
+    Ok(Some(report))
+}
+
+fn read_manifest(target: &Path) -> io::Result<HashSet<PathBuf>> {
+    match fs::read_to_string(target.join(MANIFEST_FILE)) {
+        Ok(text) => Ok(text.lines().map(PathBuf::from).collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(HashSet::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Paths that aren't valid Unicode are left out and so never removed later
+fn write_manifest<'a>(target: &Path, paths: impl Iterator<Item = &'a PathBuf>) -> io::Result<()> {
+    let mut lines: Vec<&str> = paths.filter_map(|path| path.to_str()).collect();
+    lines.sort_unstable();
+    fs::write(target.join(MANIFEST_FILE), lines.join("\n"))
+}
+
+/// Whether there was a file to remove
+fn remove_if_present(path: &Path) -> io::Result<bool> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Every file below `root` outside of `SKIPPED_DIRS`
+fn walk(root: &Path) -> io::Result<Vec<Entry>> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![PathBuf::new()];
+    while let Some(relative) = dirs.pop() {
+        for item in fs::read_dir(root.join(&relative))? {
+            let item = item?;
+            let name = item.file_name();
+            let path = relative.join(&name);
+            // Follows symlinks, so linked files are copied as regular files
+            let metadata = fs::metadata(item.path())?;
+            if metadata.is_dir() {
+                if !SKIPPED_DIRS.iter().any(|skipped| name == *skipped) {
+                    dirs.push(path);
+                }
+            } else {
+                entries.push(Entry {
+                    relative: path,
+                    len: metadata.len(),
+                    modified: metadata.modified()?,
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Identifies the state of the bundle; changes with any added, removed or modified file
+fn fingerprint(entries: &[Entry]) -> String {
+    let mut hasher = DefaultHasher::new();
+    for entry in entries {
+        entry.relative.hash(&mut hasher);
+        entry.len.hash(&mut hasher);
+        let modified = entry
+            .modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        modified.as_nanos().hash(&mut hasher);
+    }
+    format!("{}:{:016x}", entries.len(), hasher.finish())
+}
+
+fn is_current(dest: &Path, entry: &Entry) -> bool {
+    fs::metadata(dest).is_ok_and(|metadata| {
+        metadata.is_file()
+            && metadata.len() == entry.len
+            && metadata
+                .modified()
+                .is_ok_and(|modified| modified == entry.modified)
+    })
+}
+
+/// Copy through a temporary file renamed into place, so `dest` is never half
+/// written. The copy is created writable even when the bundled file is
+/// read-only, and gets the source's modification time for `is_current`.
+fn copy_file(source: &Path, dest: &Path, modified: SystemTime) -> io::Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let partial = partial_path(dest);
+    let mut file = File::create(&partial)?;
+    io::copy(&mut File::open(source)?, &mut file)?;
+    file.set_modified(modified)?;
+    drop(file);
+    fs::rename(&partial, dest)
+}
+
+fn partial_path(dest: &Path) -> PathBuf {
+    let mut partial = OsString::from(dest.as_os_str());
+    partial.push(PARTIAL_SUFFIX);
+    PathBuf::from(partial)
+}
@@ -0,0 +1,253 @@
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use crate::backend::{BackendManager, BackendPaths, BackendStats, BackendStatus, RestartPolicy};
+use crate::error::BackendError;
+use crate::log_events::LogStream;
+use crate::maintenance::MaintenanceSchedule;
+use crate::registry::ProcessInfo;
+use crate::settings::LaunchSettings;
+use crate::supervisor::Supervisor;
+
+/// Run a blocking manager operation off the async runtime.
+///
+/// The manager sleeps and waits on process events synchronously, which must not
+/// happen on a runtime worker thread.
+async fn run_blocking<T, F>(app: AppHandle, op: F) -> Result<T, BackendError>
+where
+    T: Send + 'static,
+    F: FnOnce(&BackendManager) -> Result<T, BackendError> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(move || match app.try_state::<BackendManager>() {
+        Some(manager) => op(&manager),
+        None => Err(BackendError::ManagerUnavailable),
+    })
+    .await
+    .map_err(|e| anyhow::anyhow!("Backend task failed: {e}"))?
+}
+
+/// Handle to the lifecycle queue, cloned so no manager borrow is held across awaits
+fn supervisor(app: &AppHandle) -> Result<Supervisor, BackendError> {
+    let manager = app
+        .try_state::<BackendManager>()
+        .ok_or(BackendError::ManagerUnavailable)?;
+    manager.supervisor().cloned()
+}
+
+/// Restart the backend process (bound to the "Restart Backend" menu item)
+#[tauri::command]
+pub async fn restart_backend(app: AppHandle) -> Result<(), BackendError> {
+    supervisor(&app)?.restart().await
+}
+
+/// Swap in a fresh backend without downtime and return the new API URL
+#[tauri::command]
+pub async fn reload_backend(app: AppHandle) -> Result<String, BackendError> {
+    supervisor(&app)?.reload().await
+}
+
+/// Base URL of the backend API, which changes after a reload
+#[tauri::command]
+pub fn get_backend_url(manager: State<'_, BackendManager>) -> String {
+    manager.api_url()
+}
+
+/// Start the backend if it isn't running and return its PID
+#[tauri::command]
+pub async fn start_backend(app: AppHandle) -> Result<u32, BackendError> {
+    supervisor(&app)?.start_backend().await
+}
+
+/// Start the backend with extra command line flags, e.g. `["--log-level", "debug"]`.
+/// Fails with `already_running` unless `force` is set, which stops the running one first.
+#[tauri::command]
+pub async fn start_backend_with_args(
+    app: AppHandle,
+    args: Vec<String>,
+    force: Option<bool>,
+) -> Result<(), BackendError> {
+    supervisor(&app)?.start(args, force == Some(true)).await
+}
+
+/// Stop the backend and return the PIDs that were terminated; does nothing when
+/// it isn't running
+#[tauri::command]
+pub async fn stop_backend(app: AppHandle) -> Result<Vec<u32>, BackendError> {
+    supervisor(&app)?.stop().await
+}
+
+/// Run uv sync again while the backend is stopped, streaming its output as
+/// `backend://deps-progress`; `reinstall` rebuilds every package. Fails with
+/// `already_running` while a backend process is up.
+#[tauri::command]
+pub async fn resync_dependencies(
+    app: AppHandle,
+    reinstall: Option<bool>,
+) -> Result<(), BackendError> {
+    supervisor(&app)?.resync(reinstall == Some(true)).await
+}
+
+/// Kill the backend and everything it spawned immediately, skipping the graceful
+/// shutdown; for a backend that hangs and ignores SIGINT
+#[tauri::command]
+pub async fn force_stop(app: AppHandle) -> Result<(), BackendError> {
+    supervisor(&app)?.force_stop().await
+}
+
+/// Seconds the backend has been up, or `None` when it isn't running
+#[tauri::command]
+pub fn get_backend_uptime(manager: State<'_, BackendManager>) -> Option<u64> {
+    manager.backend_uptime().map(|uptime| uptime.as_secs())
+}
+
+/// Launch an additional backend module (from a fixed allowlist) under `name`
+/// and return its PID. With `depends_on`, waits up to `startup_timeout_secs` for
+/// that process to be ready first.
+#[tauri::command]
+pub async fn start_process(
+    app: AppHandle,
+    name: String,
+    module: String,
+    args: Vec<String>,
+    depends_on: Option<String>,
+    startup_timeout_secs: Option<u64>,
+) -> Result<u32, BackendError> {
+    supervisor(&app)?
+        .start_process(
+            name,
+            module,
+            args,
+            depends_on,
+            startup_timeout_secs.map(Duration::from_secs),
+        )
+        .await
+}
+
+/// Start a service from the launch settings by name and return its PID
+#[tauri::command]
+pub async fn start_service(app: AppHandle, name: String) -> Result<u32, BackendError> {
+    supervisor(&app)?.start_service(name).await
+}
+
+/// Stop a service by name and return its PID; the same as `stop_process`
+#[tauri::command]
+pub async fn stop_service(app: AppHandle, name: String) -> Result<u32, BackendError> {
+    supervisor(&app)?.stop_process(name).await
+}
+
+/// Restart one backend process by name, leaving the others running
+#[tauri::command]
+pub async fn restart_process(app: AppHandle, name: String) -> Result<(), BackendError> {
+    supervisor(&app)?.restart_process(name).await
+}
+
+/// Stop one backend process by name and return its PID
+#[tauri::command]
+pub async fn stop_process(app: AppHandle, name: String) -> Result<u32, BackendError> {
+    supervisor(&app)?.stop_process(name).await
+}
+
+/// State, PID, uptime and restart count of one backend process
+#[tauri::command]
+pub fn get_process_status(
+    manager: State<'_, BackendManager>,
+    name: String,
+) -> Result<ProcessInfo, BackendError> {
+    manager.process_status(&name)
+}
+
+/// Name, module, PID, start time and state of every backend process
+#[tauri::command]
+pub fn list_processes(manager: State<'_, BackendManager>) -> Vec<ProcessInfo> {
+    manager.list_processes()
+}
+
+/// PIDs of the running backend processes, e.g. to look them up in Activity Monitor
+#[tauri::command]
+pub fn backend_pids(manager: State<'_, BackendManager>) -> Vec<u32> {
+    manager.backend_pids()
+}
+
+/// Report the backend lifecycle state and PID
+#[tauri::command]
+pub async fn get_backend_status(app: AppHandle) -> Result<BackendStatus, BackendError> {
+    supervisor(&app)?.status().await
+}
+
+/// Report uptime and how often the backend was (re)started since launch
+#[tauri::command]
+pub fn get_backend_stats(manager: State<'_, BackendManager>) -> BackendStats {
+    manager.stats()
+}
+
+/// Resolved backend directory and log directory
+#[tauri::command]
+pub fn backend_paths(manager: State<'_, BackendManager>) -> BackendPaths {
+    manager.paths()
+}
+
+/// Return the last `lines` lines of the backend log, stderr unless `stream` says
+/// otherwise; empty when nothing was logged yet
+#[tauri::command]
+pub async fn read_backend_log(
+    app: AppHandle,
+    lines: usize,
+    stream: Option<LogStream>,
+) -> Result<Vec<String>, BackendError> {
+    let stream = stream.unwrap_or(LogStream::Stderr);
+    run_blocking(app, move |manager| manager.read_log(stream, lines)).await
+}
+
+/// Reveal the backend log directory in the OS file manager, e.g. for bug reports
+#[tauri::command]
+pub fn open_log_dir(manager: State<'_, BackendManager>) -> Result<(), BackendError> {
+    manager.open_log_dir()
+}
+
+/// Report how the backend is handled when it dies on its own
+#[tauri::command]
+pub fn get_restart_policy(manager: State<'_, BackendManager>) -> RestartPolicy {
+    manager.restart_policy()
+}
+
+/// Change how the backend is handled when it dies on its own
+#[tauri::command]
+pub fn set_restart_policy(manager: State<'_, BackendManager>, policy: RestartPolicy) {
+    manager.set_restart_policy(policy);
+}
+
+/// Report the nightly maintenance restart schedule
+#[tauri::command]
+pub fn get_maintenance_schedule(manager: State<'_, BackendManager>) -> MaintenanceSchedule {
+    manager.maintenance_schedule()
+}
+
+/// Change and persist the nightly maintenance restart schedule
+#[tauri::command]
+pub fn set_maintenance_schedule(
+    manager: State<'_, BackendManager>,
+    schedule: MaintenanceSchedule,
+) -> Result<(), BackendError> {
+    manager.set_maintenance_schedule(schedule)
+}
+
+/// Report whether the backend starts with the app
+#[tauri::command]
+pub fn get_launch_settings(manager: State<'_, BackendManager>) -> LaunchSettings {
+    manager.launch_settings()
+}
+
+/// Change and persist whether the backend starts with the app
+#[tauri::command]
+pub fn set_launch_settings(
+    manager: State<'_, BackendManager>,
+    settings: LaunchSettings,
+) -> Result<(), BackendError> {
+    manager.set_launch_settings(settings)
+}
+
+/// Tell the manager the user is active; wakes a backend stopped for being idle
+#[tauri::command]
+pub async fn activity_heartbeat(app: AppHandle) -> Result<(), BackendError> {
+    supervisor(&app)?.heartbeat().await
+}
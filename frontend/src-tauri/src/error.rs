@@ -0,0 +1,166 @@
+//! Failures of the backend manager that callers may want to tell apart.
+
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::error::Error as _;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BackendError {
+    #[error(
+        "Backend directory not found; tried {}",
+        tried.iter().map(|path| format!("{path:?}")).collect::<Vec<_>>().join(", ")
+    )]
+    BackendDirMissing { tried: Vec<PathBuf> },
+
+    #[error(
+        "Backend directory {path:?} has neither pyproject.toml nor a valuecell package; \
+         it contains [{}]",
+        found.join(", ")
+    )]
+    BackendDirInvalid { path: PathBuf, found: Vec<String> },
+
+    #[error("Failed to copy the backend from {from:?} to {to:?}")]
+    BackendCopyFailed {
+        from: PathBuf,
+        to: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error(
+        "No working uv found (tried {}). Reinstall ValueCell, or install uv from \
+         https://docs.astral.sh/uv/getting-started/installation/ and put it on PATH or \
+         set uv_path in the launch settings.",
+        tried.join("; ")
+    )]
+    UvMissing { tried: Vec<String> },
+
+    #[error("Failed to spawn {program}")]
+    SpawnFailed {
+        program: &'static str,
+        #[source]
+        source: anyhow::Error,
+    },
+
+    #[error(
+        "Installing backend dependencies failed (code: {code:?}, signal: {signal:?}){}",
+        log_tail.iter().map(|line| format!("\n{line}")).collect::<String>()
+    )]
+    DepsSyncFailed {
+        code: Option<i32>,
+        signal: Option<i32>,
+        /// Last lines of uv-sync.log
+        log_tail: Vec<String>,
+    },
+
+    #[error("Port {port} is already in use by {owner}")]
+    PortInUse { port: u16, owner: String },
+
+    #[error("Refusing backend argument {0:?}: it contains shell metacharacters")]
+    InvalidArgument(String),
+
+    #[error("{}", match pid {
+        Some(pid) => format!("Backend is already running as process {pid}"),
+        None => "Backend is already starting".to_string(),
+    })]
+    AlreadyRunning { pid: Option<u32> },
+
+    #[error("No backend process named {0:?}")]
+    UnknownProcess(String),
+
+    #[error(
+        "Install path too long, long-path support disabled: files below {path:?} would \
+         exceed the Windows path limit. Enable LongPathsEnabled in the registry or group \
+         policy, or install ValueCell in a shorter location."
+    )]
+    #[cfg(windows)]
+    PathTooLong { path: PathBuf },
+
+    #[error("Module {0:?} is not on the list of backend modules that may be started")]
+    ModuleNotAllowed(String),
+
+    #[error("Invalid process name {0:?}: use letters, digits, '-' or '_', not starting with \"backend\"")]
+    InvalidProcessName(String),
+
+    #[error("Not starting {name}: {dependency} did not become ready ({reason})")]
+    DependencyNotReady {
+        name: String,
+        dependency: String,
+        reason: String,
+    },
+
+    #[error("Backend start cancelled, the app is exiting")]
+    StartCancelled,
+
+    #[error("Reload aborted, the previous backend keeps serving: {0}")]
+    ReloadFailed(String),
+
+    #[error("Failed to stop {failed} of {total} backend processes ({details})")]
+    StopFailed {
+        failed: usize,
+        total: usize,
+        details: String,
+    },
+
+    #[error("Backend shutdown did not finish within {0:?}")]
+    StopTimedOut(Duration),
+
+    #[error("Backend manager is not available")]
+    ManagerUnavailable,
+
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl BackendError {
+    /// Stable identifier the frontend can branch on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::BackendDirMissing { .. } => "backend_dir_missing",
+            Self::BackendDirInvalid { .. } => "backend_dir_invalid",
+            Self::BackendCopyFailed { .. } => "backend_copy_failed",
+            Self::UvMissing { .. } => "uv_missing",
+            Self::SpawnFailed { .. } => "spawn_failed",
+            Self::DepsSyncFailed { .. } => "deps_sync_failed",
+            Self::PortInUse { .. } => "port_in_use",
+            Self::InvalidArgument(_) => "invalid_argument",
+            Self::AlreadyRunning { .. } => "already_running",
+            Self::UnknownProcess(_) => "unknown_process",
+            #[cfg(windows)]
+            Self::PathTooLong { .. } => "path_too_long",
+            Self::ModuleNotAllowed(_) => "module_not_allowed",
+            Self::InvalidProcessName(_) => "invalid_process_name",
+            Self::DependencyNotReady { .. } => "dependency_not_ready",
+            Self::StartCancelled => "start_cancelled",
+            Self::ReloadFailed(_) => "reload_failed",
+            Self::StopFailed { .. } => "stop_failed",
+            Self::StopTimedOut(_) => "stop_timed_out",
+            Self::ManagerUnavailable => "manager_unavailable",
+            Self::Other(_) => "other",
+        }
+    }
+
+    /// The message followed by all of its causes, like anyhow's `{:#}`
+    pub fn report(&self) -> String {
+        let mut report = self.to_string();
+        let mut source = self.source();
+        while let Some(cause) = source {
+            report.push_str(": ");
+            report.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        report
+    }
+}
+
+/// Sent to the frontend as `{ kind, message }`
+impl Serialize for BackendError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("BackendError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.report())?;
+        state.end()
+    }
+}
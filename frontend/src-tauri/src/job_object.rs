@@ -0,0 +1,78 @@
+//! Windows Job Object that ties the lifetime of the backend tree to the app.
+//!
+//! With `JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE`, the OS kills every process in the
+//! job once its last handle closes, which also happens when the app crashes or
+//! is killed from Task Manager.
+
+use std::io;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+use windows_sys::Win32::System::JobObjects::{
+    AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+    SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+    JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+};
+use windows_sys::Win32::System::Threading::{OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE};
+
+/// Owned job handle; closing it kills whatever is still assigned
+pub struct JobObject(HANDLE);
+
+// The handle is only passed to thread-safe kernel calls
+unsafe impl Send for JobObject {}
+unsafe impl Sync for JobObject {}
+
+impl JobObject {
+    /// An anonymous job that kills its processes when the last handle closes
+    pub fn kill_on_close() -> io::Result<Self> {
+        // SAFETY: null attributes and name create an unnamed job with default security
+        let handle = unsafe { CreateJobObjectW(ptr::null(), ptr::null()) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        let job = Self(handle);
+
+        let mut limits = JOBOBJECT_EXTENDED_LIMIT_INFORMATION::default();
+        limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+        // SAFETY: `limits` is a valid value of the type the class expects, and the
+        // length passed is its size
+        let set = unsafe {
+            SetInformationJobObject(
+                job.0,
+                JobObjectExtendedLimitInformation,
+                ptr::from_ref(&limits).cast(),
+                size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            )
+        };
+        if set == 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(job)
+    }
+
+    /// Put `pid` into the job; processes it starts from now on join it too
+    pub fn assign(&self, pid: u32) -> io::Result<()> {
+        // SAFETY: plain FFI call, the returned handle is checked and closed below
+        let process = unsafe { OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid) };
+        if process.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: both handles are open for the duration of the call
+        let assigned = unsafe { AssignProcessToJobObject(self.0, process) };
+        let result = if assigned == 0 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        };
+        // SAFETY: `process` was opened above and isn't used afterwards
+        unsafe { CloseHandle(process) };
+        result
+    }
+}
+
+impl Drop for JobObject {
+    fn drop(&mut self) {
+        // SAFETY: the handle is owned by this value and closed exactly once
+        unsafe { CloseHandle(self.0) };
+    }
+}
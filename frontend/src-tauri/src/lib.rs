@@ -1,7 +1,68 @@
+mod arch;
 mod backend;
+mod backend_copy;
+mod commands;
+mod error;
+#[cfg(windows)]
+mod job_object;
+mod log_events;
+mod log_file;
+#[cfg(windows)]
+mod long_path;
+mod maintenance;
+mod process_tree;
+mod registry;
+mod settings;
+mod signals;
+mod supervisor;
+#[cfg(windows)]
+mod win_process;
 
 use backend::BackendManager;
-use tauri::Manager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{Emitter, Manager};
+
+/// Set once the backend was stopped for an exit request, so the repeated request isn't held again
+static EXIT_CLEANUP_DONE: AtomicBool = AtomicBool::new(false);
+
+/// Label of the window declared in tauri.conf.json
+const MAIN_WINDOW_LABEL: &str = "main";
+/// Set to `1` to stop the backend with the main window even while other windows stay open
+const STOP_WITH_MAIN_WINDOW_ENV: &str = "VALUECELL_STOP_WITH_MAIN_WINDOW";
+
+/// Whether closing `window` should stop the backend: when no other visible window
+/// is left, or when it is the main window and `VALUECELL_STOP_WITH_MAIN_WINDOW` is set
+fn is_last_window(window: &tauri::Window) -> bool {
+    let stop_with_main = std::env::var(STOP_WITH_MAIN_WINDOW_ENV)
+        .is_ok_and(|v| matches!(v.trim(), "1" | "true" | "yes"));
+    let windows = window.app_handle().webview_windows();
+    let open = windows
+        .iter()
+        .map(|(label, w)| (label.as_str(), w.is_visible().unwrap_or(true)));
+    closes_last_window(window.label(), open, stop_with_main)
+}
+
+/// `is_last_window` for `label`, given the open windows as label and visibility
+fn closes_last_window<'a>(
+    label: &str,
+    mut open: impl Iterator<Item = (&'a str, bool)>,
+    stop_with_main: bool,
+) -> bool {
+    if stop_with_main && label == MAIN_WINDOW_LABEL {
+        return true;
+    }
+    !open.any(|(other, visible)| other != label && visible)
+}
+
+/// Set while the cleanup started by a destroyed window runs
+static WINDOW_CLEANUP_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Claim `running` for a cleanup; `false` when one is already in progress
+fn claim_cleanup(running: &AtomicBool) -> bool {
+    running
+        .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -15,43 +76,222 @@ pub fn run() {
         )
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
+        .invoke_handler(tauri::generate_handler![
+            commands::restart_backend,
+            commands::reload_backend,
+            commands::get_backend_url,
+            commands::start_backend,
+            commands::start_backend_with_args,
+            commands::stop_backend,
+            commands::force_stop,
+            commands::resync_dependencies,
+            commands::get_backend_status,
+            commands::list_processes,
+            commands::backend_pids,
+            commands::start_process,
+            commands::start_service,
+            commands::stop_service,
+            commands::restart_process,
+            commands::stop_process,
+            commands::get_process_status,
+            commands::get_backend_stats,
+            commands::get_backend_uptime,
+            commands::read_backend_log,
+            commands::open_log_dir,
+            commands::backend_paths,
+            commands::get_restart_policy,
+            commands::set_restart_policy,
+            commands::get_maintenance_schedule,
+            commands::set_maintenance_schedule,
+            commands::get_launch_settings,
+            commands::set_launch_settings,
+            commands::activity_heartbeat
+        ])
         .setup(|app| {
             let handle = app.handle().clone();
+            // Before the first start, which may spend a while in uv sync
+            signals::spawn_handler(handle.clone());
 
             let manager = match BackendManager::new(handle) {
                 Ok(manager) => manager,
                 Err(e) => {
-                    log::error!("❌ Failed to create backend manager: {e:#}");
+                    log::error!("❌ Failed to create backend manager: {}", e.report());
                     return Ok(());
                 }
             };
 
-            if let Err(e) = manager.start_all() {
-                log::error!("❌ Failed to start backend: {e:#}");
-            }
-
+            // Register the manager before spawning so crash handling can reach it
             app.manage(manager);
 
+            let manager = app.state::<BackendManager>();
+            manager.spawn_supervisor();
+            if !manager.launch_settings().auto_start {
+                log::info!("Auto-start is off, the backend waits for start_backend");
+            } else {
+                // Queued like a start from the UI, so setup doesn't wait for uv sync
+                match manager.supervisor().cloned() {
+                    Ok(supervisor) => {
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = supervisor.start_backend().await {
+                                log::error!("❌ Failed to start backend: {}", e.report());
+                            }
+                        });
+                    }
+                    Err(e) => log::error!("❌ Failed to start backend: {}", e.report()),
+                }
+            }
+            manager.spawn_maintenance_scheduler();
+            manager.spawn_resume_watcher();
+            manager.spawn_idle_watcher();
+
             Ok(())
         })
         .on_window_event(|window, event| {
-            // Handle window close events to ensure proper cleanup
+            // Keep the last window open until the backend has stopped, so the UI
+            // doesn't freeze and the OS doesn't tear things down mid-kill
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+                let Some(manager) = window.app_handle().try_state::<BackendManager>() else {
+                    return;
+                };
+                if !is_last_window(window) || !manager.has_process() {
+                    return;
+                }
+                api.prevent_close();
+                log::info!("Close requested, stopping backend before closing the window...");
+                if let Err(e) = window.emit("backend://shutting-down", ()) {
+                    log::warn!("Failed to emit backend://shutting-down: {}", e);
+                }
+                let window = window.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(manager) = window.app_handle().try_state::<BackendManager>() {
+                        // Bounded by the shutdown deadline, after which processes are killed
+                        if let Err(e) = manager.shutdown().await {
+                            log::error!("❌ Backend cleanup failed: {}", e.report());
+                        }
+                    }
+                    // destroy() skips CloseRequested, so this doesn't loop back here
+                    if let Err(e) = window.destroy() {
+                        log::error!("Failed to close window {:?}: {}", window.label(), e);
+                    }
+                });
+                return;
+            }
+
+            // Fallback for windows that go away without a close request
             if let tauri::WindowEvent::Destroyed = event {
-                log::info!("Window destroyed, ensuring backend cleanup...");
-                if let Some(manager) = window.app_handle().try_state::<BackendManager>() {
-                    manager.stop_all();
+                if !is_last_window(window) {
+                    log::info!(
+                        "Window {:?} destroyed, other windows remain",
+                        window.label()
+                    );
+                    return;
                 }
+                // Windows closing in quick succession may both get here; only the
+                // first starts a cleanup
+                if !claim_cleanup(&WINDOW_CLEANUP_RUNNING) {
+                    log::info!(
+                        "Window {:?} destroyed, backend cleanup already running",
+                        window.label()
+                    );
+                    return;
+                }
+                log::info!("Window destroyed, ensuring backend cleanup...");
+                // Don't hold up the event loop; RunEvent::Exit waits for this to finish
+                let app = window.app_handle().clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Some(manager) = app.try_state::<BackendManager>() {
+                        if let Err(e) = manager.shutdown().await {
+                            log::error!("❌ Backend cleanup failed: {}", e.report());
+                        }
+                    }
+                    WINDOW_CLEANUP_RUNNING.store(false, Ordering::SeqCst);
+                });
             }
         })
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
         .run(|app_handle, event| {
-            // Handle app exit events (e.g., Cmd+Q on Mac)
+            // Cmd+Q and friends: hold the exit until the backend is gone, then ask again
+            if let tauri::RunEvent::ExitRequested { code, api, .. } = &event {
+                let Some(manager) = app_handle.try_state::<BackendManager>() else {
+                    return;
+                };
+                // The second request, after cleanup, always goes through
+                if EXIT_CLEANUP_DONE.load(Ordering::SeqCst) || !manager.has_process() {
+                    return;
+                }
+                api.prevent_exit();
+                log::info!("Exit requested, stopping backend first...");
+                let app = app_handle.clone();
+                let code = code.unwrap_or(0);
+                tauri::async_runtime::spawn(async move {
+                    if let Some(manager) = app.try_state::<BackendManager>() {
+                        if let Err(e) = manager.shutdown_bounded().await {
+                            log::error!("❌ Backend cleanup failed: {}", e.report());
+                        }
+                    }
+                    EXIT_CLEANUP_DONE.store(true, Ordering::SeqCst);
+                    app.exit(code);
+                });
+                return;
+            }
+
+            // Last resort when the exit wasn't requested through the event above
             if let tauri::RunEvent::Exit = event {
                 log::info!("Application exiting, cleaning up backend...");
                 if let Some(manager) = app_handle.try_state::<BackendManager>() {
-                    manager.stop_all();
+                    manager.cancel_start();
+                    if let Err(e) = manager.stop_all() {
+                        log::error!("❌ Backend cleanup failed: {}", e.report());
+                    }
                 }
             }
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_last_visible_window_stops_the_backend() {
+        let both = [("main", true), ("settings", true)];
+        assert!(!closes_last_window("main", both.into_iter(), false));
+        assert!(!closes_last_window("settings", both.into_iter(), false));
+        // Hidden windows don't keep the backend alive
+        let hidden = [("main", true), ("settings", false)];
+        assert!(closes_last_window("main", hidden.into_iter(), false));
+        assert!(closes_last_window(
+            "settings",
+            [("settings", true)].into_iter(),
+            false
+        ));
+    }
+
+    #[test]
+    fn main_window_stops_the_backend_when_configured() {
+        let both = [("main", true), ("settings", true)];
+        assert!(closes_last_window("main", both.into_iter(), true));
+        assert!(!closes_last_window("settings", both.into_iter(), true));
+    }
+
+    #[test]
+    fn windows_destroyed_together_start_one_cleanup() {
+        let running = AtomicBool::new(false);
+        // Both saw themselves as the last window
+        let claimed = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..2)
+                .map(|_| scope.spawn(|| claim_cleanup(&running)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .filter(|&claimed| claimed)
+                .count()
+        });
+        assert_eq!(claimed, 1);
+        // Released once the cleanup finished
+        running.store(false, Ordering::SeqCst);
+        assert!(claim_cleanup(&running));
+    }
+}
@@ -0,0 +1,299 @@
+//! Batched forwarding of backend output to the frontend as `backend://log` events.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// How often pending lines are flushed as one event
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+/// Upper bound on lines per event; a full batch is flushed early
+const MAX_BATCH_LINES: usize = 200;
+/// Lines buffered between the reader and the emitter before new ones are dropped
+const QUEUE_CAPACITY: usize = 2000;
+/// Longest line kept whole; longer output without a newline is split at this size
+const MAX_LINE_BYTES: usize = 64 * 1024;
+/// Leading words of a line searched for a level name, enough to get past a timestamp
+/// and logger name
+const LEVEL_SEARCH_WORDS: usize = 8;
+
+/// Which pipe a line came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// Reassembles lines from output chunks that may end anywhere, even inside a
+/// UTF-8 character, so that a line is only decoded once it is complete
+#[derive(Default)]
+pub struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// The lines `chunk` completes, without their `\n` or `\r\n`. A line longer
+    /// than `MAX_LINE_BYTES` is cut there, at a character boundary.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(chunk);
+        let mut lines = Vec::new();
+        let mut consumed = 0;
+        loop {
+            let rest = &self.pending[consumed..];
+            let newline = rest
+                .iter()
+                .take(MAX_LINE_BYTES + 1)
+                .position(|&b| b == b'\n');
+            match newline {
+                Some(end) => {
+                    let line = &rest[..end];
+                    lines.push(decode(line.strip_suffix(b"\r").unwrap_or(line)));
+                    consumed += end + 1;
+                }
+                None if rest.len() > MAX_LINE_BYTES => {
+                    let cut = char_boundary(rest, MAX_LINE_BYTES);
+                    lines.push(decode(&rest[..cut]));
+                    consumed += cut;
+                }
+                None => break,
+            }
+        }
+        self.pending.drain(..consumed);
+        lines
+    }
+
+    /// What is left once the output has ended, as a last line
+    pub fn finish(&mut self) -> Option<String> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let rest = std::mem::take(&mut self.pending);
+        Some(decode(rest.strip_suffix(b"\r").unwrap_or(&rest)))
+    }
+}
+
+/// Invalid bytes, which a complete line only has when the backend wrote them,
+/// become replacement characters
+fn decode(line: &[u8]) -> String {
+    String::from_utf8_lossy(line).into_owned()
+}
+
+/// The largest index up to `max` that doesn't split a UTF-8 character, or `max`
+/// when the bytes before it aren't UTF-8 anyway
+fn char_boundary(bytes: &[u8], max: usize) -> usize {
+    // A character is at most 4 bytes, its later bytes look like 0b10xxxxxx
+    (max.saturating_sub(3)..=max)
+        .rev()
+        .find(|&i| bytes.get(i).is_some_and(|&b| b & 0xc0 != 0x80))
+        .filter(|&i| i > 0)
+        .unwrap_or(max)
+}
+
+/// Level of a line from Python logging, e.g. `ERROR:root:...`, uvicorn's
+/// `INFO:     ...` or `2024-01-01 12:00:00,000 - app - WARNING - ...`: the first
+/// upper case level name among the leading words, `None` when there is none
+pub fn parse_level(line: &str) -> Option<log::Level> {
+    line.split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .take(LEVEL_SEARCH_WORDS)
+        .find_map(|word| match word {
+            "CRITICAL" | "FATAL" | "ERROR" => Some(log::Level::Error),
+            "WARNING" | "WARN" => Some(log::Level::Warn),
+            "INFO" | "SUCCESS" => Some(log::Level::Info),
+            "DEBUG" => Some(log::Level::Debug),
+            "TRACE" => Some(log::Level::Trace),
+            _ => None,
+        })
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct LogLine {
+    stream: LogStream,
+    line: String,
+}
+
+/// Payload of the `backend://log` event
+#[derive(Debug, Clone, Serialize)]
+struct LogBatchPayload {
+    pid: u32,
+    lines: Vec<LogLine>,
+    /// Lines discarded since the previous batch because the queue was full
+    dropped: usize,
+}
+
+/// Hands lines to a background emitter so a chatty backend can neither flood the
+/// event bus nor stall the reader. Dropping the forwarder flushes what is left.
+pub struct LogForwarder {
+    tx: SyncSender<LogLine>,
+    dropped: Arc<AtomicUsize>,
+}
+
+impl LogForwarder {
+    pub fn spawn<R: Runtime>(app: AppHandle<R>, pid: u32) -> Self {
+        let (tx, rx) = mpsc::sync_channel::<LogLine>(QUEUE_CAPACITY);
+        let dropped = Arc::new(AtomicUsize::new(0));
+        let counter = dropped.clone();
+
+        std::thread::spawn(move || {
+            let mut batch = Vec::new();
+            let mut deadline = Instant::now() + FLUSH_INTERVAL;
+            loop {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                let disconnected = match rx.recv_timeout(timeout) {
+                    Ok(line) => {
+                        batch.push(line);
+                        false
+                    }
+                    Err(RecvTimeoutError::Timeout) => false,
+                    Err(RecvTimeoutError::Disconnected) => true,
+                };
+
+                if disconnected || batch.len() >= MAX_BATCH_LINES || Instant::now() >= deadline {
+                    let dropped = counter.swap(0, Ordering::Relaxed);
+                    if !batch.is_empty() || dropped > 0 {
+                        let payload = LogBatchPayload {
+                            pid,
+                            lines: std::mem::take(&mut batch),
+                            dropped,
+                        };
+                        if let Err(e) = app.emit("backend://log", payload) {
+                            log::warn!("Failed to emit backend://log: {}", e);
+                        }
+                    }
+                    deadline = Instant::now() + FLUSH_INTERVAL;
+                }
+
+                if disconnected {
+                    break;
+                }
+            }
+        });
+
+        Self { tx, dropped }
+    }
+
+    pub fn send(&self, stream: LogStream, line: &str) {
+        let line = LogLine {
+            stream,
+            line: line.to_string(),
+        };
+        match self.tx.try_send(line) {
+            Ok(()) | Err(TrySendError::Disconnected(_)) => {}
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_split_across_pushes_comes_out_once() {
+        let mut buffer = LineBuffer::default();
+        assert!(buffer.push(b"INFO: star").is_empty());
+        assert_eq!(buffer.push(b"ted\nnext"), ["INFO: started"]);
+        assert_eq!(buffer.finish().as_deref(), Some("next"));
+    }
+
+    #[test]
+    fn crlf_split_between_chunks_ends_one_line() {
+        let mut buffer = LineBuffer::default();
+        assert!(buffer.push(b"first\r").is_empty());
+        assert_eq!(buffer.push(b"\nsecond\r\n"), ["first", "second"]);
+        assert_eq!(buffer.finish(), None);
+    }
+
+    #[test]
+    fn characters_split_at_every_boundary_are_decoded_whole() {
+        // 2-, 3- and 4-byte characters
+        let line = "é市🚀 ready";
+        let bytes = format!("{}\n", line).into_bytes();
+        for i in 0..=bytes.len() {
+            let mut buffer = LineBuffer::default();
+            let mut lines = buffer.push(&bytes[..i]);
+            lines.extend(buffer.push(&bytes[i..]));
+            assert_eq!(lines, [line], "split at byte {}", i);
+            assert_eq!(buffer.finish(), None, "split at byte {}", i);
+        }
+    }
+
+    #[test]
+    fn long_line_is_cut_before_a_character_crossing_the_limit() {
+        // 市 takes bytes MAX_LINE_BYTES - 1 to MAX_LINE_BYTES + 1
+        let long = "a".repeat(MAX_LINE_BYTES - 1);
+        let mut buffer = LineBuffer::default();
+        let lines = buffer.push(format!("{}市场", long).as_bytes());
+        assert_eq!(lines, [long]);
+        assert_eq!(buffer.push(b"\n"), ["市场"]);
+    }
+
+    #[test]
+    fn finish_flushes_a_trailing_partial_line() {
+        let mut buffer = LineBuffer::default();
+        assert_eq!(buffer.push(b"done\nTraceback (most"), ["done"]);
+        assert!(buffer.push(b" recent call last)\r").is_empty());
+        assert_eq!(
+            buffer.finish().as_deref(),
+            Some("Traceback (most recent call last)")
+        );
+        assert_eq!(buffer.finish(), None);
+    }
+
+    #[test]
+    fn parse_level_reads_uvicorn_prefixes() {
+        assert_eq!(
+            parse_level("INFO:     Uvicorn running on http://127.0.0.1:8000"),
+            Some(log::Level::Info)
+        );
+        assert_eq!(
+            parse_level("WARNING:  Invalid HTTP request received."),
+            Some(log::Level::Warn)
+        );
+    }
+
+    #[test]
+    fn parse_level_reads_the_default_logging_format() {
+        assert_eq!(
+            parse_level("ERROR:root:Failed to connect to the exchange"),
+            Some(log::Level::Error)
+        );
+    }
+
+    #[test]
+    fn parse_level_reads_asctime_formatted_lines() {
+        assert_eq!(
+            parse_level("2024-01-01 12:00:00,000 - valuecell.agents - WARNING - rate limited"),
+            Some(log::Level::Warn)
+        );
+        assert_eq!(
+            parse_level("2024-01-01 12:00:00,000 - valuecell.server - CRITICAL - out of memory"),
+            Some(log::Level::Error)
+        );
+    }
+
+    #[test]
+    fn parse_level_ignores_lines_without_a_level() {
+        assert_eq!(parse_level("Resolved 142 packages in 3ms"), None);
+        assert_eq!(parse_level(""), None);
+        // Only upper case names count
+        assert_eq!(parse_level("an error occurred"), None);
+    }
+
+    #[test]
+    fn parse_level_ignores_level_names_past_the_leading_words() {
+        let words = ["word"; LEVEL_SEARCH_WORDS].join(" ");
+        assert_eq!(parse_level(&format!("{} ERROR", words)), None);
+        // The last word still searched
+        let words = ["word"; LEVEL_SEARCH_WORDS - 1].join(" ");
+        assert_eq!(
+            parse_level(&format!("{} ERROR", words)),
+            Some(log::Level::Error)
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Size-rotated log files for backend output.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use chrono::Utc;
+
+/// ISO-8601 UTC with millisecond precision
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S%.3fZ";
+
+/// Current UTC time formatted for log line prefixes
+pub fn timestamp() -> String {
+    Utc::now().format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Append-only log file that rotates to `<name>.1`, `<name>.2`, ... once it grows
+/// past `max_bytes`, keeping at most `max_files` rotated files.
+pub struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    max_files: usize,
+    timestamps: bool,
+}
+
+impl RotatingFile {
+    pub fn open(path: PathBuf, max_bytes: u64, max_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes,
+            max_files,
+            timestamps: false,
+        })
+    }
+
+    /// Prefix every written line with the current UTC time
+    pub fn timestamped(mut self, enabled: bool) -> Self {
+        self.timestamps = enabled;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Open the file again, e.g. after it was deleted or a write failed
+    pub fn reopen(&mut self) -> io::Result<()> {
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = self.file.metadata()?.len();
+        Ok(())
+    }
+
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let line = if self.timestamps {
+            format!("{} {}", timestamp(), line)
+        } else {
+            line.to_string()
+        };
+
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{}", line)?;
+        self.size += len;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            // Shift name.N-1 -> name.N, ..., name -> name.1; the oldest is overwritten
+            for index in (1..self.max_files).rev() {
+                let from = rotated_path(&self.path, index);
+                if from.exists() {
+                    fs::rename(&from, rotated_path(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated_path(&self.path, 1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Read the last `lines` lines of `path` by scanning backwards from the end in
+/// chunks. A missing file has no lines.
+pub fn tail(path: &Path, lines: usize) -> io::Result<Vec<String>> {
+    const CHUNK: u64 = 8 * 1024;
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut pos = file.metadata()?.len();
+    let mut buf = Vec::new();
+    // One extra newline is needed since the last line is terminated too
+    while pos > 0 && buf.iter().filter(|&&b| b == b'\n').count() <= lines {
+        let len = CHUNK.min(pos);
+        pos -= len;
+        let mut chunk = vec![0; len as usize];
+        file.seek(SeekFrom::Start(pos))?;
+        file.read_exact(&mut chunk)?;
+        chunk.append(&mut buf);
+        buf = chunk;
+    }
+
+    let text = String::from_utf8_lossy(&buf);
+    let all: Vec<&str> = text.lines().collect();
+    // A first line cut by the chunk boundary only occurs with more than `lines` lines, so it's skipped
+    let skip = all.len().saturating_sub(lines);
+    Ok(all[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+/// Delete backend logs in `dir` (`backend*.log`, its rotations and JSON logs) last
+/// modified more than `max_age` ago. Other files are never touched. Returns what
+/// was removed.
+pub fn prune(dir: &Path, max_age: Duration) -> io::Result<Vec<PathBuf>> {
+    let now = SystemTime::now();
+    let mut removed = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        let owned = name.starts_with("backend")
+            && (name.ends_with(".log") || name.contains(".log.") || name.contains(".jsonl"));
+        if !owned || !entry.file_type()?.is_file() {
+            continue;
+        }
+        let modified = entry.metadata()?.modified()?;
+        if now.duration_since(modified).unwrap_or_default() > max_age {
+            fs::remove_file(entry.path())?;
+            removed.push(entry.path());
+        }
+    }
+    Ok(removed)
+}
@@ -0,0 +1,79 @@
+//! Windows MAX_PATH handling for the backend and log directories.
+//!
+//! uv creates `.venv` deep below the backend directory, so an install location
+//! that is only moderately long already pushes package files past the legacy
+//! 260 character limit. Such paths need the `\\?\` form and the system-wide
+//! `LongPathsEnabled` policy, which Python and uv both honor.
+
+use std::ffi::OsStr;
+use std::fs;
+use std::iter;
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+use windows_sys::Win32::System::Registry::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_DWORD};
+
+/// Limit of the Win32 APIs without long-path support, in UTF-16 units
+pub const LEGACY_MAX_PATH: usize = 260;
+
+/// `path` as it should be handed to the backend, given that files up to
+/// `headroom` characters deeper are created below it: unchanged when those fit
+/// the legacy limit, in canonical `\\?\` form when long paths are enabled, and
+/// `None` when they are needed but disabled. `path` must exist.
+pub fn fit(path: &Path, headroom: usize) -> Option<PathBuf> {
+    let length = path.as_os_str().encode_wide().count();
+    if length + headroom < LEGACY_MAX_PATH {
+        return Some(path.to_path_buf());
+    }
+    if !long_paths_enabled() {
+        log::error!(
+            "{:?} is {} characters long, {} with its contents, and long paths are disabled",
+            path,
+            length,
+            length + headroom
+        );
+        return None;
+    }
+    // canonicalize returns the `\\?\C:\...` or `\\?\UNC\...` form
+    match fs::canonicalize(path) {
+        Ok(verbatim) => {
+            log::info!("Using long path form {:?}", verbatim);
+            Some(verbatim)
+        }
+        Err(e) => {
+            log::warn!("Failed to canonicalize {:?}, using it as is: {}", path, e);
+            Some(path.to_path_buf())
+        }
+    }
+}
+
+/// Whether `HKLM\SYSTEM\CurrentControlSet\Control\FileSystem\LongPathsEnabled` is set
+fn long_paths_enabled() -> bool {
+    let key = wide("SYSTEM\\CurrentControlSet\\Control\\FileSystem");
+    let value = wide("LongPathsEnabled");
+    let mut data: u32 = 0;
+    let mut size = size_of::<u32>() as u32;
+    // SAFETY: both names are NUL-terminated and `data` has room for the `size`
+    // bytes of a REG_DWORD
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_DWORD,
+            ptr::null_mut(),
+            ptr::from_mut(&mut data).cast(),
+            &mut size,
+        )
+    };
+    status == ERROR_SUCCESS && data == 1
+}
+
+fn wide(text: &str) -> Vec<u16> {
+    OsStr::new(text)
+        .encode_wide()
+        .chain(iter::once(0))
+        .collect()
+}
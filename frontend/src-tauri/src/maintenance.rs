@@ -0,0 +1,115 @@
+//! Persisted schedule for the nightly maintenance restart of the backend.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveTime};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::settings::{load_json, save_json};
+
+/// Restart the backend once a day at a local wall-clock time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaintenanceSchedule {
+    pub enabled: bool,
+    pub hour: u32,
+    pub minute: u32,
+}
+
+impl Default for MaintenanceSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: 4,
+            minute: 30,
+        }
+    }
+}
+
+impl MaintenanceSchedule {
+    fn time(&self) -> Result<NaiveTime> {
+        NaiveTime::from_hms_opt(self.hour, self.minute, 0)
+            .ok_or_else(|| anyhow!("Invalid restart time {:02}:{:02}", self.hour, self.minute))
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.time().map(|_| ())
+    }
+
+    /// First occurrence of the configured time strictly after `now`, or `None`
+    /// when the schedule is disabled
+    pub fn next_after(&self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        if !self.enabled {
+            return None;
+        }
+        let time = self.time().ok()?;
+        (0..=2).find_map(|days| {
+            let date = now.date_naive() + ChronoDuration::days(days);
+            // A time skipped by a DST change falls back to an hour later
+            let at = date
+                .and_time(time)
+                .and_local_timezone(Local)
+                .earliest()
+                .or_else(|| {
+                    (date.and_time(time) + ChronoDuration::hours(1))
+                        .and_local_timezone(Local)
+                        .earliest()
+                })?;
+            (at > now).then_some(at)
+        })
+    }
+
+    /// Read the schedule saved by a previous run; a missing file means the default
+    pub fn load(path: &Path) -> Result<Self> {
+        let schedule: Self = load_json(path)?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_json(path, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// Mid-June, away from any DST change
+    fn june_15(hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 6, 15, hour, minute, 0)
+            .single()
+            .unwrap()
+    }
+
+    fn at(hour: u32, minute: u32) -> MaintenanceSchedule {
+        MaintenanceSchedule {
+            enabled: true,
+            hour,
+            minute,
+        }
+    }
+
+    #[test]
+    fn next_after_picks_a_later_time_today() {
+        assert_eq!(at(12, 30).next_after(june_15(10, 0)), Some(june_15(12, 30)));
+    }
+
+    #[test]
+    fn next_after_rolls_a_passed_time_to_tomorrow() {
+        let tomorrow = june_15(4, 30) + ChronoDuration::days(1);
+        assert_eq!(at(4, 30).next_after(june_15(10, 0)), Some(tomorrow));
+        // Strictly after: the restart that is due now isn't scheduled again
+        assert_eq!(at(4, 30).next_after(june_15(4, 30)), Some(tomorrow));
+    }
+
+    #[test]
+    fn next_after_is_none_when_disabled() {
+        let schedule = MaintenanceSchedule {
+            enabled: false,
+            ..at(12, 30)
+        };
+        assert_eq!(schedule.next_after(june_15(10, 0)), None);
+    }
+}
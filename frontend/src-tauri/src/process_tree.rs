@@ -0,0 +1,426 @@
+//! Helpers for inspecting the process tree spawned under the backend.
+
+use std::collections::HashSet;
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(unix)]
+use std::io;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+#[cfg(windows)]
+pub use crate::win_process::{command_line, process_alive};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Whether any process spawned under `parent_pid` is still running.
+///
+/// When the probe itself can't run, the tree is assumed to be alive so callers
+/// keep escalating rather than leaking processes.
+#[cfg(unix)]
+pub fn descendants_alive(parent_pid: u32) -> bool {
+    process_table().is_none_or(|rows| !descendant_pids(&rows, parent_pid).is_empty())
+}
+
+/// Whether the process tree rooted at `parent_pid` is still running.
+///
+/// Windows doesn't reparent orphans, so a python.exe whose uv parent is gone is
+/// still found through the stale parent PID.
+#[cfg(windows)]
+pub fn descendants_alive(parent_pid: u32) -> bool {
+    match process_table() {
+        Some(rows) => {
+            rows.iter().any(|row| row.0 == parent_pid)
+                || !descendant_pids(&rows, parent_pid).is_empty()
+        }
+        None => process_alive(parent_pid),
+    }
+}
+
+/// Everything spawned under `parent_pid`, at any depth and children first;
+/// empty when the process table can't be read
+pub fn descendants(parent_pid: u32) -> Vec<u32> {
+    process_table().map_or_else(Vec::new, |rows| descendant_pids(&rows, parent_pid))
+}
+
+/// Descendants of `parent_pid` still running, plus those of `known` (an earlier
+/// snapshot of them) that have been reparented since; all of `known` when the
+/// process table can't be read
+pub fn surviving(parent_pid: u32, known: &[u32]) -> Vec<u32> {
+    let Some(rows) = process_table() else {
+        return known.to_vec();
+    };
+    let mut pids = descendant_pids(&rows, parent_pid);
+    for &pid in known {
+        if !pids.contains(&pid) && rows.iter().any(|row| row.0 == pid) {
+            pids.push(pid);
+        }
+    }
+    pids
+}
+
+/// Whether `pid` is running; assumed so when the probe can't run
+#[cfg(unix)]
+pub fn process_alive(pid: u32) -> bool {
+    // Signal 0 only checks that the process exists and may be signaled
+    send_signal(pid, 0).unwrap_or(true)
+}
+
+/// Deliver `signal` to `pid` with kill(2). `Ok(false)` when there is no such process.
+#[cfg(unix)]
+pub fn send_signal(pid: u32, signal: libc::c_int) -> io::Result<bool> {
+    // 0 and anything that wraps negative would signal whole process groups
+    let pid = libc::pid_t::try_from(pid)
+        .ok()
+        .filter(|&pid| pid > 0)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid PID"))?;
+    // SAFETY: kill takes plain integers and has no memory-safety requirements
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        return Ok(true);
+    }
+    let error = io::Error::last_os_error();
+    match error.raw_os_error() {
+        Some(libc::ESRCH) => Ok(false),
+        // It exists, we just may not signal it
+        Some(libc::EPERM) if signal == 0 => Ok(true),
+        _ => Err(error),
+    }
+}
+
+/// Full command line of `pid`, or `None` when it isn't running or can't be inspected
+#[cfg(unix)]
+pub fn command_line(pid: u32) -> Option<String> {
+    let output = Command::new("ps")
+        .args(["-o", "command=", "-p", &pid.to_string()])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let line = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !line.is_empty()).then_some(line)
+}
+
+/// PID of the process listening on TCP `port`, if it can be found
+#[cfg(unix)]
+pub fn port_owner(port: u16) -> Option<u32> {
+    let output = Command::new("lsof")
+        .args(["-nP", &format!("-iTCP:{}", port), "-sTCP:LISTEN", "-t"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.trim().parse().ok())
+}
+
+/// PID of the process listening on TCP `port`, if it can be found
+#[cfg(windows)]
+pub fn port_owner(port: u16) -> Option<u32> {
+    let output = hidden_command("netstat")
+        .args(["-ano", "-p", "TCP"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let suffix = format!(":{}", port);
+    // Proto  Local Address  Foreign Address  State  PID
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(
+            |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [_, local, _, "LISTENING", pid] if local.ends_with(&suffix) => pid.parse().ok(),
+                _ => None,
+            },
+        )
+}
+
+/// Resident memory of `root_pid` and everything spawned under it, in bytes;
+/// `None` when the process table can't be read or `root_pid` isn't in it
+#[cfg(unix)]
+pub fn tree_memory_bytes(root_pid: u32) -> Option<u64> {
+    sum_tree(&process_table()?, root_pid)
+}
+
+/// `pid ppid rss-bytes` of every process that isn't a zombie, from /proc
+#[cfg(target_os = "linux")]
+fn process_table() -> Option<Vec<(u32, u32, u64)>> {
+    // SAFETY: sysconf only reads a system constant
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    let page_size = u64::try_from(page_size).ok().filter(|&size| size > 0)?;
+    let rows = proc_stats()?
+        .into_iter()
+        .filter(|stat| !matches!(stat.state, 'Z' | 'X'))
+        .map(|stat| (stat.pid, stat.ppid, stat.rss_pages * page_size))
+        .collect();
+    Some(rows)
+}
+
+/// Children of this app that exited but were never waited on
+#[cfg(all(debug_assertions, target_os = "linux"))]
+pub fn zombie_children() -> Vec<u32> {
+    let own_pid = std::process::id();
+    proc_stats()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|stat| stat.state == 'Z' && stat.ppid == own_pid)
+        .map(|stat| stat.pid)
+        .collect()
+}
+
+/// The fields of /proc/<pid>/stat used here
+#[cfg(target_os = "linux")]
+struct ProcStat {
+    pid: u32,
+    state: char,
+    ppid: u32,
+    rss_pages: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn proc_stats() -> Option<Vec<ProcStat>> {
+    let entries = fs::read_dir("/proc").ok()?;
+    let stats = entries
+        .filter_map(|entry| {
+            let pid: u32 = entry.ok()?.file_name().to_str()?.parse().ok()?;
+            // Gone between listing and reading, or not ours to read
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+            // The command name in parentheses may itself contain spaces and ')'
+            let fields: Vec<&str> = stat
+                .get(stat.rfind(')')? + 1..)?
+                .split_whitespace()
+                .collect();
+            Some(ProcStat {
+                pid,
+                state: fields.first()?.chars().next()?,
+                ppid: fields.get(1)?.parse().ok()?,
+                rss_pages: fields.get(21)?.parse().ok()?,
+            })
+        })
+        .collect();
+    Some(stats)
+}
+
+/// Collect the exit status of those of `pids` that are our own exited children,
+/// so they don't stay behind as zombies. Others are left alone.
+#[cfg(unix)]
+pub fn reap(pids: &[u32]) -> Vec<u32> {
+    pids.iter()
+        .copied()
+        .filter(|&pid| {
+            let Ok(raw) = libc::pid_t::try_from(pid) else {
+                return false;
+            };
+            let mut status = 0;
+            // SAFETY: `status` outlives the call; WNOHANG never blocks and fails
+            // with ECHILD for processes that aren't our children
+            raw > 0 && unsafe { libc::waitpid(raw, &mut status, libc::WNOHANG) } == raw
+        })
+        .collect()
+}
+
+/// `pid ppid rss-bytes` of every process that isn't a zombie, from ps
+#[cfg(all(unix, not(target_os = "linux")))]
+fn process_table() -> Option<Vec<(u32, u32, u64)>> {
+    let output = Command::new("ps")
+        .args(["-A", "-o", "pid=,ppid=,rss=,stat="])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let table: String = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let mut fields: Vec<_> = line.split_whitespace().collect();
+            let stat = fields.pop()?;
+            (!stat.starts_with('Z')).then(|| fields.join(" ") + "\n")
+        })
+        .collect();
+    // rss is reported in KiB
+    Some(parse_process_rows(&table, 1024))
+}
+
+/// Working set of `root_pid` and everything spawned under it, in bytes;
+/// `None` when the process table can't be read or `root_pid` isn't in it
+#[cfg(windows)]
+pub fn tree_memory_bytes(root_pid: u32) -> Option<u64> {
+    let mut rows = process_table()?;
+    let tree: HashSet<u32> = descendant_pids(&rows, root_pid)
+        .into_iter()
+        .chain([root_pid])
+        .collect();
+    for row in rows.iter_mut().filter(|row| tree.contains(&row.0)) {
+        row.2 = crate::win_process::working_set(row.0).unwrap_or(0);
+    }
+    sum_tree(&rows, root_pid)
+}
+
+/// `pid ppid 0` of every process, from a ToolHelp snapshot. Reading a working
+/// set takes a handle per process, so `tree_memory_bytes` fills in only those
+/// of the tree it sums.
+#[cfg(windows)]
+fn process_table() -> Option<Vec<(u32, u32, u64)>> {
+    let rows = crate::win_process::processes()?;
+    Some(rows.into_iter().map(|(pid, ppid)| (pid, ppid, 0)).collect())
+}
+
+/// `pid ppid memory` lines, with memory multiplied by `unit` bytes
+#[cfg(all(unix, not(target_os = "linux")))]
+fn parse_process_rows(table: &str, unit: u64) -> Vec<(u32, u32, u64)> {
+    table
+        .lines()
+        .filter_map(
+            |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                [pid, ppid, memory] => Some((
+                    pid.parse().ok()?,
+                    ppid.parse().ok()?,
+                    memory.parse::<u64>().ok()? * unit,
+                )),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+fn sum_tree(rows: &[(u32, u32, u64)], root_pid: u32) -> Option<u64> {
+    let root = rows.iter().find(|row| row.0 == root_pid)?.2;
+    let descendants = descendant_pids(rows, root_pid);
+    let memory = rows
+        .iter()
+        .filter(|row| descendants.contains(&row.0))
+        .map(|row| row.2);
+    Some(root + memory.sum::<u64>())
+}
+
+/// PIDs below `root_pid` in `pid ppid ...` rows, nearest first
+fn descendant_pids(rows: &[(u32, u32, u64)], root_pid: u32) -> Vec<u32> {
+    // Windows keeps stale parent PIDs, which can be reused and form a cycle
+    let mut seen = HashSet::from([root_pid]);
+    let mut descendants = Vec::new();
+    let mut next = 0;
+    let mut parent = root_pid;
+    loop {
+        for &(pid, ..) in rows.iter().filter(|row| row.1 == parent) {
+            if seen.insert(pid) {
+                descendants.push(pid);
+            }
+        }
+        let Some(&pid) = descendants.get(next) else {
+            return descendants;
+        };
+        parent = pid;
+        next += 1;
+    }
+}
+
+/// A console tool that doesn't flash a window when run from the GUI app
+#[cfg(windows)]
+fn hidden_command(program: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    let mut command = Command::new(program);
+    command.creation_flags(CREATE_NO_WINDOW);
+    command
+}
+
+/// Poll until the tree under `parent_pid` is gone or `timeout` elapses.
+/// Returns `true` when nothing survived.
+#[cfg(windows)]
+pub async fn wait_for_descendants_exit(parent_pid: u32, timeout: Duration) -> bool {
+    wait_until(timeout, || !descendants_alive(parent_pid)).await
+}
+
+/// Poll until nothing `surviving` reports is left or `timeout` elapses.
+/// Returns `true` when nothing survived.
+pub async fn wait_for_tree_exit(parent_pid: u32, known: &[u32], timeout: Duration) -> bool {
+    wait_until(timeout, || surviving(parent_pid, known).is_empty()).await
+}
+
+/// Poll until `pid` is gone or `timeout` elapses. Returns `true` when it exited.
+pub async fn wait_for_process_exit(pid: u32, timeout: Duration) -> bool {
+    wait_until(timeout, || !process_alive(pid)).await
+}
+
+async fn wait_until(timeout: Duration, mut done: impl FnMut() -> bool) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if done() {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn wait_for_tree_exit_returns_once_the_tree_is_gone() {
+        let mut shell = Command::new("sh")
+            .args(["-c", "sleep 0.5 & wait"])
+            .spawn()
+            .unwrap();
+        let pid = shell.id();
+        let forked = tauri::async_runtime::block_on(wait_until(Duration::from_secs(5), || {
+            !descendants(pid).is_empty()
+        }));
+        assert!(forked, "sh never forked sleep");
+        let known = descendants(pid);
+
+        let started = Instant::now();
+        let timeout = Duration::from_secs(10);
+        assert!(tauri::async_runtime::block_on(wait_for_tree_exit(
+            pid, &known, timeout
+        )));
+        // Returned when sleep exited, not at the timeout
+        assert!(started.elapsed() < Duration::from_secs(5));
+        shell.wait().unwrap();
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn reap_waits_on_exited_children_only() {
+        let child = Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        // Dropping the handle doesn't wait, so the child stays a zombie until reaped
+        drop(child);
+        let reaped = tauri::async_runtime::block_on(wait_until(Duration::from_secs(5), || {
+            !reap(&[pid]).is_empty()
+        }));
+        assert!(reaped, "child {} was never reaped", pid);
+
+        // Nothing is left to wait for
+        let mut status = 0;
+        // SAFETY: `status` outlives the call and WNOHANG never blocks
+        let waited = unsafe { libc::waitpid(pid as libc::pid_t, &mut status, libc::WNOHANG) };
+        assert_eq!(waited, -1);
+        assert_eq!(
+            io::Error::last_os_error().raw_os_error(),
+            Some(libc::ECHILD)
+        );
+        #[cfg(target_os = "linux")]
+        assert!(!std::path::Path::new(&format!("/proc/{}", pid)).exists());
+        // Not our child
+        assert!(reap(&[1]).is_empty());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn process_table_lists_a_spawned_tree() {
+        let mut cmd = hidden_command("cmd")
+            .args(["/C", "ping -n 30 127.0.0.1 > NUL"])
+            .spawn()
+            .unwrap();
+        let pid = cmd.id();
+        let rows = process_table().unwrap();
+        assert!(rows.contains(&(pid, std::process::id(), 0)));
+        assert!(tree_memory_bytes(pid).is_some_and(|bytes| bytes > 0));
+        cmd.kill().unwrap();
+        cmd.wait().unwrap();
+    }
+}
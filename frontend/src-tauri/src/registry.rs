@@ -0,0 +1,304 @@
+//! Named registry of the processes the manager spawned.
+//!
+//! The registry is owned by the supervisor task; everyone else reads the
+//! snapshot it publishes after every change.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::Instant;
+use tauri_plugin_shell::process::CommandChild;
+use tokio::sync::watch;
+
+use crate::backend::BackendState;
+
+/// Name of the API server the frontend talks to
+pub const PRIMARY_PROCESS: &str = "backend";
+
+/// A spawned process together with what it is and when it started
+pub struct ProcessHandle {
+    pub name: String,
+    /// Python module passed to `uv run -m`
+    pub module: String,
+    /// Arguments after the module, reused when the process is restarted
+    pub args: Vec<String>,
+    pub pid: u32,
+    /// Milliseconds since the Unix epoch
+    pub started_at: u64,
+    pub started: Instant,
+    pub state: BackendState,
+    /// Name of the process that has to be ready before this one starts
+    pub depends_on: Option<String>,
+    /// Port the process was started on, for its shutdown request
+    pub port: Option<u16>,
+    pub child: CommandChild,
+    /// Turns `true` once the process exited; closed when its output ended
+    pub exited: watch::Receiver<bool>,
+}
+
+impl ProcessHandle {
+    pub fn pid(&self) -> u32 {
+        self.pid
+    }
+
+    /// `name (pid N)`, for log messages
+    pub fn label(&self) -> String {
+        format!("{} (pid {})", self.name, self.pid)
+    }
+}
+
+/// Returned by `list_processes`: a `ProcessHandle` without the child handle
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessInfo {
+    pub name: String,
+    pub module: String,
+    pub pid: u32,
+    pub started_at: u64,
+    pub uptime_secs: u64,
+    #[serde(skip)]
+    pub started: Instant,
+    pub state: BackendState,
+    pub depends_on: Option<String>,
+    /// Restarts of the process under this name since the app was launched
+    pub restart_count: u32,
+}
+
+/// The registry as published after its last change, in start order
+#[derive(Debug, Clone, Default)]
+pub struct ProcessSnapshot {
+    processes: Vec<ProcessInfo>,
+}
+
+impl ProcessSnapshot {
+    /// PID of the process serving the frontend
+    pub fn primary_pid(&self) -> Option<u32> {
+        self.pid_of(PRIMARY_PROCESS)
+    }
+
+    pub fn pid_of(&self, name: &str) -> Option<u32> {
+        self.info(name).map(|p| p.pid)
+    }
+
+    pub fn state_of(&self, name: &str) -> Option<BackendState> {
+        self.info(name).map(|p| p.state)
+    }
+
+    pub fn name_of(&self, pid: u32) -> Option<&str> {
+        self.processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| p.name.as_str())
+    }
+
+    pub fn contains(&self, pid: u32) -> bool {
+        self.name_of(pid).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+
+    /// PIDs in start order
+    pub fn pids(&self) -> Vec<u32> {
+        self.processes.iter().map(|p| p.pid).collect()
+    }
+
+    pub fn info(&self, name: &str) -> Option<ProcessInfo> {
+        self.processes
+            .iter()
+            .find(|p| p.name == name)
+            .map(Self::current)
+    }
+
+    pub fn list(&self) -> Vec<ProcessInfo> {
+        self.processes.iter().map(Self::current).collect()
+    }
+
+    /// `info` with the uptime as of now rather than as of the last change
+    fn current(info: &ProcessInfo) -> ProcessInfo {
+        ProcessInfo {
+            uptime_secs: info.started.elapsed().as_secs(),
+            ..info.clone()
+        }
+    }
+}
+
+/// Processes keyed by name, in start order
+pub struct ProcessRegistry {
+    processes: Vec<ProcessHandle>,
+    /// Kept by name, so the count survives the process being replaced
+    restarts: HashMap<String, u32>,
+    view: watch::Sender<ProcessSnapshot>,
+}
+
+impl ProcessRegistry {
+    /// An empty registry that publishes every change to `view`
+    pub fn new(view: watch::Sender<ProcessSnapshot>) -> Self {
+        let registry = Self {
+            processes: Vec::new(),
+            restarts: HashMap::new(),
+            view,
+        };
+        registry.publish();
+        registry
+    }
+
+    /// Register `handle` unless its name is taken, in which case it is handed
+    /// back so the caller can dispose of the process
+    pub fn insert(&mut self, handle: ProcessHandle) -> Result<(), Box<ProcessHandle>> {
+        if self.pid_of(&handle.name).is_some() {
+            return Err(Box::new(handle));
+        }
+        log::info!("Process {} added to process registry", handle.label());
+        self.processes.push(handle);
+        self.publish();
+        Ok(())
+    }
+
+    /// PID of the process serving the frontend
+    pub fn primary_pid(&self) -> Option<u32> {
+        self.pid_of(PRIMARY_PROCESS)
+    }
+
+    pub fn pid_of(&self, name: &str) -> Option<u32> {
+        self.processes
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.pid)
+    }
+
+    /// State of the process registered as `name`
+    pub fn state_of(&self, name: &str) -> Option<BackendState> {
+        self.processes
+            .iter()
+            .find(|p| p.name == name)
+            .map(|p| p.state)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ProcessHandle> {
+        self.processes.iter().find(|p| p.name == name)
+    }
+
+    pub fn contains_name(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Names in start order
+    pub fn names(&self) -> Vec<String> {
+        self.processes.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn record_restart(&mut self, name: &str) {
+        *self.restarts.entry(name.to_string()).or_default() += 1;
+        self.publish();
+    }
+
+    pub fn name_of(&self, pid: u32) -> Option<&str> {
+        self.find(pid).map(|p| p.name.as_str())
+    }
+
+    pub fn contains(&self, pid: u32) -> bool {
+        self.find(pid).is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+
+    /// Receiver that turns `true` once `pid` exited
+    pub fn exit_watch(&self, pid: u32) -> Option<watch::Receiver<bool>> {
+        self.find(pid).map(|p| p.exited.clone())
+    }
+
+    pub fn take_named(&mut self, name: &str) -> Option<ProcessHandle> {
+        let index = self.processes.iter().position(|p| p.name == name)?;
+        let handle = self.processes.remove(index);
+        self.publish();
+        Some(handle)
+    }
+
+    pub fn take(&mut self, pid: u32) -> Option<ProcessHandle> {
+        let index = self.processes.iter().position(|p| p.pid == pid)?;
+        let handle = self.processes.remove(index);
+        self.publish();
+        Some(handle)
+    }
+
+    /// Remove every process, dependents ahead of the processes they depend on
+    /// so they can be stopped in that order
+    pub fn drain(&mut self) -> Vec<ProcessHandle> {
+        let mut drained = Vec::with_capacity(self.processes.len());
+        while !self.processes.is_empty() {
+            let needed = |name: &str| {
+                self.processes
+                    .iter()
+                    .any(|p| p.depends_on.as_deref() == Some(name))
+            };
+            // A dependency cycle falls back to start order
+            let index = self
+                .processes
+                .iter()
+                .position(|p| !needed(&p.name))
+                .unwrap_or(0);
+            drained.push(self.processes.remove(index));
+        }
+        self.publish();
+        drained
+    }
+
+    /// Make `pid` the serving process under `name`; `false` when `pid` isn't
+    /// tracked or another process holds `name`
+    pub fn promote(&mut self, pid: u32, name: &str) -> bool {
+        if self.pid_of(name).is_some_and(|holder| holder != pid) {
+            return false;
+        }
+        let Some(mut handle) = self.take(pid) else {
+            return false;
+        };
+        handle.name = name.to_string();
+        self.processes.insert(0, handle);
+        self.publish();
+        true
+    }
+
+    pub fn set_state(&mut self, pid: u32, state: BackendState) {
+        if let Some(handle) = self.processes.iter_mut().find(|p| p.pid == pid) {
+            handle.state = state;
+            self.publish();
+        }
+    }
+
+    pub fn set_depends_on(&mut self, pid: u32, dependency: Option<&str>) {
+        if let Some(handle) = self.processes.iter_mut().find(|p| p.pid == pid) {
+            handle.depends_on = dependency.map(str::to_string);
+            self.publish();
+        }
+    }
+
+    pub fn list(&self) -> Vec<ProcessInfo> {
+        self.processes.iter().map(|p| self.info_of(p)).collect()
+    }
+
+    fn info_of(&self, p: &ProcessHandle) -> ProcessInfo {
+        ProcessInfo {
+            name: p.name.clone(),
+            module: p.module.clone(),
+            pid: p.pid,
+            started_at: p.started_at,
+            uptime_secs: p.started.elapsed().as_secs(),
+            started: p.started,
+            state: p.state,
+            depends_on: p.depends_on.clone(),
+            restart_count: self.restarts.get(&p.name).copied().unwrap_or(0),
+        }
+    }
+
+    fn find(&self, pid: u32) -> Option<&ProcessHandle> {
+        self.processes.iter().find(|p| p.pid == pid)
+    }
+
+    fn publish(&self) {
+        self.view.send_replace(ProcessSnapshot {
+            processes: self.list(),
+        });
+    }
+}
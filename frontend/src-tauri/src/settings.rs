@@ -0,0 +1,72 @@
+//! Persisted launch preferences of the backend manager, and the JSON file
+//! handling shared with the other persisted settings.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchSettings {
+    /// Start the backend when the app launches; when off it waits for `start_backend`
+    pub auto_start: bool,
+    /// Processes started next to the API server by `start_all`, in this order
+    pub services: Vec<ServiceConfig>,
+    /// uv to use when neither the bundled one nor one on PATH works
+    pub uv_path: Option<PathBuf>,
+}
+
+impl Default for LaunchSettings {
+    fn default() -> Self {
+        Self {
+            auto_start: true,
+            services: Vec::new(),
+            uv_path: None,
+        }
+    }
+}
+
+/// A backend module run as its own named process, e.g. a worker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceConfig {
+    pub name: String,
+    pub module: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Started once this process is ready, usually `"backend"`
+    #[serde(default)]
+    pub depends_on: Option<String>,
+}
+
+impl LaunchSettings {
+    /// Read the settings saved by a previous run; a missing file means the default
+    pub fn load(path: &Path) -> Result<Self> {
+        load_json(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_json(path, self)
+    }
+}
+
+/// Read a value saved by `save_json`; a missing file means the default
+pub fn load_json<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match fs::read_to_string(path) {
+        Ok(contents) => {
+            serde_json::from_str(&contents).with_context(|| format!("Failed to parse {:?}", path))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {:?}", path)),
+    }
+}
+
+/// Write `value` as pretty-printed JSON, creating the directory it goes in
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {:?}", dir))?;
+    }
+    fs::write(path, serde_json::to_vec_pretty(value)?)
+        .with_context(|| format!("Failed to write {:?}", path))
+}
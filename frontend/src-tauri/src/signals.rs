@@ -0,0 +1,83 @@
+//! Termination signals sent to the app itself.
+//!
+//! systemd, a logout or `kill <pid>` end the app with SIGTERM or SIGHUP, and a
+//! terminal with SIGINT, none of which produce window or exit events. Caught
+//! here, they stop the backend the way quitting does before the app exits. On
+//! Windows the console control events are handled the same way; a GUI build
+//! has no console, and its backend is covered by the job object instead.
+
+use std::future::Future;
+use std::sync::atomic::Ordering;
+
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+
+use crate::backend::BackendManager;
+
+/// Listen for termination signals for the rest of the app's life
+pub fn spawn_handler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let name = match wait_for_signal().await {
+            Ok(name) => name,
+            Err(e) => {
+                log::warn!("Failed to install signal handlers: {}", e);
+                return;
+            }
+        };
+        log::info!("Received {}, stopping backend before exiting...", name);
+        if let Some(manager) = app.try_state::<BackendManager>() {
+            // A start in progress, e.g. during uv sync, would spawn after the cleanup
+            manager.cancel_start();
+            if let Err(e) = manager.shutdown_bounded().await {
+                log::error!("❌ Backend cleanup failed: {}", e.report());
+            }
+        }
+        crate::EXIT_CLEANUP_DONE.store(true, Ordering::SeqCst);
+        app.exit(0);
+    });
+}
+
+/// Name of the first termination signal received
+#[cfg(unix)]
+async fn wait_for_signal() -> std::io::Result<&'static str> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    relay(&tx, "SIGTERM", async move { terminate.recv().await });
+    relay(&tx, "SIGINT", async move { interrupt.recv().await });
+    relay(&tx, "SIGHUP", async move { hangup.recv().await });
+    Ok(rx.recv().await.unwrap_or("signal"))
+}
+
+/// Name of the first console control event received
+#[cfg(windows)]
+async fn wait_for_signal() -> std::io::Result<&'static str> {
+    use tokio::signal::windows::{ctrl_break, ctrl_c, ctrl_close};
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut c = ctrl_c()?;
+    let mut brk = ctrl_break()?;
+    // Windows ends the process shortly after delivering this one
+    let mut close = ctrl_close()?;
+    relay(&tx, "Ctrl+C", async move { c.recv().await });
+    relay(&tx, "Ctrl+Break", async move { brk.recv().await });
+    relay(&tx, "console close", async move { close.recv().await });
+    Ok(rx.recv().await.unwrap_or("console event"))
+}
+
+/// Send `name` once `received` resolves with a signal
+fn relay(
+    tx: &mpsc::UnboundedSender<&'static str>,
+    name: &'static str,
+    received: impl Future<Output = Option<()>> + Send + 'static,
+) {
+    let tx = tx.clone();
+    tauri::async_runtime::spawn(async move {
+        if received.await.is_some() {
+            let _ = tx.send(name);
+        }
+    });
+}
@@ -0,0 +1,571 @@
+//! The supervisor task, sole owner of the backend processes.
+//!
+//! It holds the process registry and the crash restart count, and is the only
+//! place processes are started, stopped or replaced. Requests from the UI and
+//! events from the threads watching the processes (an exit, the outcome of the
+//! readiness wait, a backend that stopped answering or uses too much memory)
+//! arrive as messages and are handled one at a time in arrival order, so a
+//! restart after a crash can't race a stop from the UI. Each runs on a blocking
+//! thread because the manager waits on processes synchronously; the registry goes
+//! along and comes back, also when the command panicked.
+//!
+//! A shutdown of the app is taken ahead of the queued messages. Status reads,
+//! force stops and heartbeats are answered as they arrive instead: a status poll
+//! must not wait behind a dependency sync that takes minutes, and a force stop is
+//! meant for exactly the backend a queued request is stuck on. Both work from the
+//! registry snapshot published after every change. State changes are broadcast
+//! to subscribers and forwarded to the webview as `backend://status`.
+
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_shell::process::TerminatedPayload;
+use tokio::sync::{mpsc, oneshot, watch};
+
+use crate::backend::{BackendManager, BackendState, BackendStatus};
+use crate::error::BackendError;
+use crate::registry::{ProcessRegistry, ProcessSnapshot, PRIMARY_PROCESS};
+
+type Reply<T> = oneshot::Sender<Result<T, BackendError>>;
+
+/// Requests understood by the supervisor task
+pub enum Command {
+    Start {
+        args: Vec<String>,
+        /// Stop a running backend first instead of failing with `AlreadyRunning`
+        force: bool,
+        reply: Reply<()>,
+    },
+    /// Start unless running, answering with the PID either way
+    StartBackend {
+        reply: Reply<u32>,
+    },
+    Stop {
+        reply: Reply<Vec<u32>>,
+    },
+    /// A stop because the app is going away; taken ahead of queued messages
+    Shutdown {
+        reply: Reply<Vec<u32>>,
+    },
+    Restart {
+        reply: Reply<()>,
+    },
+    Reload {
+        reply: Reply<String>,
+    },
+    Resync {
+        reinstall: bool,
+        reply: Reply<()>,
+    },
+    /// Queued once the dependency is ready
+    StartProcess {
+        name: String,
+        module: String,
+        args: Vec<String>,
+        depends_on: Option<String>,
+        startup_timeout: Option<Duration>,
+        reply: Reply<u32>,
+    },
+    /// Looked up in the launch settings on arrival
+    StartService {
+        name: String,
+        reply: Reply<u32>,
+    },
+    StopProcess {
+        name: String,
+        reply: Reply<u32>,
+    },
+    RestartProcess {
+        name: String,
+        reply: Reply<()>,
+    },
+    /// Not queued
+    ForceStop {
+        reply: Reply<()>,
+    },
+    /// Recorded on arrival; only waking an idle-stopped backend is queued
+    Heartbeat {
+        reply: Reply<()>,
+    },
+    /// Not queued
+    Status {
+        reply: Reply<BackendStatus>,
+    },
+    /// The output of `pid` ended with its exit
+    Exited {
+        pid: u32,
+        exit: TerminatedPayload,
+        uptime: Duration,
+    },
+    /// `pid` passed its readiness check
+    Ready {
+        pid: u32,
+    },
+    /// `pid` didn't become ready in time
+    StartupFailed {
+        pid: u32,
+        reason: String,
+    },
+    /// Replace `pid`, which stopped answering or outgrew the memory limit
+    Respawn {
+        pid: u32,
+    },
+    /// Kill the wedged `pid` outright; its exit then goes through the restart policy
+    Kill {
+        pid: u32,
+    },
+    /// Spawn a backend in place of one that crashed, once the backoff passed
+    Replace,
+    /// The restart policy changed, so the crash budget starts over
+    ResetRestarts,
+    /// Run a closure on the supervisor's thread, with the registry at hand
+    #[cfg(test)]
+    Run(Box<dyn FnOnce(&mut ProcessRegistry) + Send>),
+}
+
+/// What the supervisor task owns
+pub struct Supervised {
+    pub processes: ProcessRegistry,
+    /// Crash restarts of the API server since the last start from the UI
+    pub restart_attempts: u32,
+}
+
+/// Cheap handle that sends commands to the supervisor task
+#[derive(Clone)]
+pub struct Supervisor {
+    tx: mpsc::UnboundedSender<Command>,
+    state: watch::Sender<BackendState>,
+    processes: watch::Sender<ProcessSnapshot>,
+}
+
+impl Supervisor {
+    pub fn spawn<R: Runtime>(app: AppHandle<R>) -> Self {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Command>();
+        let (queue, mut queued) = mpsc::unbounded_channel::<Command>();
+        let (urgent, mut urgent_queued) = mpsc::unbounded_channel::<Command>();
+        let (state, _) = watch::channel(BackendState::Stopped);
+        let (processes, _) = watch::channel(ProcessSnapshot::default());
+
+        let dispatcher = app.clone();
+        let inbox = tx.clone();
+        tauri::async_runtime::spawn(async move {
+            while let Some(command) = rx.recv().await {
+                match command {
+                    // Answered next to whatever the queue is busy with
+                    Command::Status { .. } | Command::ForceStop { .. } => {
+                        let app = dispatcher.clone();
+                        tauri::async_runtime::spawn_blocking(move || {
+                            match app.try_state::<BackendManager<R>>() {
+                                Some(manager) => execute_unqueued(&manager, command),
+                                None => reject(command),
+                            }
+                        });
+                    }
+                    Command::Heartbeat { reply } => {
+                        let wake = dispatcher
+                            .try_state::<BackendManager<R>>()
+                            .is_some_and(|manager| manager.record_activity());
+                        match wake {
+                            true => enqueue(&queue, Command::Heartbeat { reply }),
+                            false => {
+                                let _ = reply.send(Ok(()));
+                            }
+                        }
+                    }
+                    Command::Shutdown { .. } => enqueue(&urgent, command),
+                    Command::StartService { name, reply } => {
+                        if let Some(command) = resolve_service(&dispatcher, name, reply) {
+                            if let Err(mpsc::error::SendError(command)) = inbox.send(command) {
+                                reject(command);
+                            }
+                        }
+                    }
+                    // Waiting here keeps the queue free for the dependency's own start
+                    Command::StartProcess {
+                        depends_on: Some(ref dependency),
+                        ref name,
+                        startup_timeout,
+                        ..
+                    } => {
+                        let (app, queue) = (dispatcher.clone(), queue.clone());
+                        let (name, dependency) = (name.clone(), dependency.clone());
+                        tauri::async_runtime::spawn(async move {
+                            let ready = match app.try_state::<BackendManager<R>>() {
+                                Some(manager) => {
+                                    manager
+                                        .wait_for_dependency(&name, &dependency, startup_timeout)
+                                        .await
+                                }
+                                None => Err(BackendError::ManagerUnavailable),
+                            };
+                            match ready {
+                                Ok(()) => enqueue(&queue, command),
+                                Err(e) => fail(command, e),
+                            }
+                        });
+                    }
+                    command => enqueue(&queue, command),
+                }
+            }
+        });
+
+        let worker = app.clone();
+        let view = processes.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut supervised = Supervised {
+                processes: ProcessRegistry::new(view),
+                restart_attempts: 0,
+            };
+            loop {
+                let command = tokio::select! {
+                    biased;
+                    Some(command) = urgent_queued.recv() => command,
+                    Some(command) = queued.recv() => command,
+                    else => break,
+                };
+                let app = worker.clone();
+                let done = tauri::async_runtime::spawn_blocking(move || {
+                    // The processes stay tracked, so a later stop still finds them
+                    let run = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        run(&app, &mut supervised, command)
+                    }));
+                    if run.is_err() {
+                        log::error!("Supervisor command panicked, keeping its processes");
+                    }
+                    supervised
+                })
+                .await;
+                match done {
+                    Ok(returned) => supervised = returned,
+                    Err(e) => {
+                        log::error!("Supervisor stopped: {}", e);
+                        return;
+                    }
+                }
+            }
+        });
+
+        let supervisor = Self {
+            tx,
+            state,
+            processes,
+        };
+        let mut states = supervisor.subscribe();
+        tauri::async_runtime::spawn(async move {
+            while states.changed().await.is_ok() {
+                let handle = app.clone();
+                let status = tauri::async_runtime::spawn_blocking(move || {
+                    handle
+                        .try_state::<BackendManager<R>>()
+                        .map(|manager| manager.status())
+                })
+                .await;
+                if let Ok(Some(status)) = status {
+                    if let Err(e) = app.emit("backend://status", status) {
+                        log::error!("Failed to emit backend://status: {}", e);
+                    }
+                }
+            }
+        });
+
+        supervisor
+    }
+
+    /// Broadcast a state change to subscribers
+    pub fn publish(&self, state: BackendState) {
+        self.state.send_replace(state);
+    }
+
+    /// Receiver of every state change from now on
+    pub fn subscribe(&self) -> watch::Receiver<BackendState> {
+        self.state.subscribe()
+    }
+
+    /// The tracked processes as of the last change to the registry
+    pub fn processes(&self) -> ProcessSnapshot {
+        self.processes.borrow().clone()
+    }
+
+    /// Hand an event to the supervisor task; dropped once the task is gone
+    pub fn notify(&self, event: Command) {
+        let _ = self.tx.send(event);
+    }
+
+    /// `notify` once `delay` has passed, without holding up the queue meanwhile
+    pub fn notify_after(&self, delay: Duration, event: Command) {
+        let tx = self.tx.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(delay).await;
+            let _ = tx.send(event);
+        });
+    }
+
+    pub async fn start(&self, args: Vec<String>, force: bool) -> Result<(), BackendError> {
+        self.request(|reply| Command::Start { args, force, reply })
+            .await
+    }
+
+    pub async fn start_backend(&self) -> Result<u32, BackendError> {
+        self.request(|reply| Command::StartBackend { reply }).await
+    }
+
+    pub async fn stop(&self) -> Result<Vec<u32>, BackendError> {
+        self.request(|reply| Command::Stop { reply }).await
+    }
+
+    pub async fn shutdown(&self) -> Result<Vec<u32>, BackendError> {
+        self.request(|reply| Command::Shutdown { reply }).await
+    }
+
+    pub async fn restart(&self) -> Result<(), BackendError> {
+        self.request(|reply| Command::Restart { reply }).await
+    }
+
+    pub async fn reload(&self) -> Result<String, BackendError> {
+        self.request(|reply| Command::Reload { reply }).await
+    }
+
+    pub async fn resync(&self, reinstall: bool) -> Result<(), BackendError> {
+        self.request(|reply| Command::Resync { reinstall, reply })
+            .await
+    }
+
+    pub async fn start_process(
+        &self,
+        name: String,
+        module: String,
+        args: Vec<String>,
+        depends_on: Option<String>,
+        startup_timeout: Option<Duration>,
+    ) -> Result<u32, BackendError> {
+        self.request(|reply| Command::StartProcess {
+            name,
+            module,
+            args,
+            depends_on,
+            startup_timeout,
+            reply,
+        })
+        .await
+    }
+
+    pub async fn start_service(&self, name: String) -> Result<u32, BackendError> {
+        self.request(|reply| Command::StartService { name, reply })
+            .await
+    }
+
+    pub async fn stop_process(&self, name: String) -> Result<u32, BackendError> {
+        self.request(|reply| Command::StopProcess { name, reply })
+            .await
+    }
+
+    pub async fn restart_process(&self, name: String) -> Result<(), BackendError> {
+        self.request(|reply| Command::RestartProcess { name, reply })
+            .await
+    }
+
+    pub async fn force_stop(&self) -> Result<(), BackendError> {
+        self.request(|reply| Command::ForceStop { reply }).await
+    }
+
+    pub async fn heartbeat(&self) -> Result<(), BackendError> {
+        self.request(|reply| Command::Heartbeat { reply }).await
+    }
+
+    pub async fn status(&self) -> Result<BackendStatus, BackendError> {
+        self.request(|reply| Command::Status { reply }).await
+    }
+
+    async fn request<T>(
+        &self,
+        command: impl FnOnce(Reply<T>) -> Command,
+    ) -> Result<T, BackendError> {
+        let (reply, rx) = oneshot::channel();
+        self.send(command(reply))?;
+        rx.await.map_err(|_| BackendError::ManagerUnavailable)?
+    }
+
+    fn send(&self, command: Command) -> Result<(), BackendError> {
+        self.tx
+            .send(command)
+            .map_err(|_| BackendError::ManagerUnavailable)
+    }
+}
+
+fn enqueue(queue: &mpsc::UnboundedSender<Command>, command: Command) {
+    if let Err(mpsc::error::SendError(command)) = queue.send(command) {
+        reject(command);
+    }
+}
+
+/// The start the service configured as `name` amounts to, or `None` once the
+/// request was answered because there is no such service
+fn resolve_service<R: Runtime>(
+    app: &AppHandle<R>,
+    name: String,
+    reply: Reply<u32>,
+) -> Option<Command> {
+    let Some(manager) = app.try_state::<BackendManager<R>>() else {
+        let _ = reply.send(Err(BackendError::ManagerUnavailable));
+        return None;
+    };
+    if name == PRIMARY_PROCESS {
+        return Some(Command::StartBackend { reply });
+    }
+    match manager.service(&name) {
+        Ok(service) => Some(Command::StartProcess {
+            name: service.name,
+            module: service.module,
+            args: service.args,
+            depends_on: service.depends_on,
+            startup_timeout: None,
+            reply,
+        }),
+        Err(e) => {
+            let _ = reply.send(Err(e));
+            None
+        }
+    }
+}
+
+fn run<R: Runtime>(app: &AppHandle<R>, supervised: &mut Supervised, command: Command) {
+    match app.try_state::<BackendManager<R>>() {
+        Some(manager) => execute(&manager, supervised, command),
+        None => reject(command),
+    }
+}
+
+fn execute<R: Runtime>(manager: &BackendManager<R>, supervised: &mut Supervised, command: Command) {
+    let Supervised {
+        processes,
+        restart_attempts,
+    } = supervised;
+    // A caller that gave up waiting no longer needs the answer
+    match command {
+        Command::Start { args, force, reply } => {
+            *restart_attempts = 0;
+            let result = match force {
+                true => manager.start_forced(processes, &args),
+                false => manager.start_with_args(processes, &args),
+            };
+            let _ = reply.send(result);
+        }
+        Command::StartBackend { reply } => {
+            *restart_attempts = 0;
+            let _ = reply.send(manager.start_backend(processes));
+        }
+        Command::Stop { reply } | Command::Shutdown { reply } => {
+            let _ = reply.send(manager.stop_tracked(processes));
+        }
+        Command::Restart { reply } => {
+            *restart_attempts = 0;
+            let _ = reply.send(manager.restart_backend(processes));
+        }
+        Command::Reload { reply } => {
+            let _ = reply.send(manager.reload_backend(processes));
+        }
+        Command::Resync { reinstall, reply } => {
+            let _ = reply.send(manager.resync_dependencies(processes, reinstall));
+        }
+        Command::StartProcess {
+            name,
+            module,
+            args,
+            depends_on,
+            reply,
+            ..
+        } => {
+            let result =
+                manager.start_process(processes, &name, &module, &args, depends_on.as_deref());
+            let _ = reply.send(result);
+        }
+        Command::StartService { name, reply } => {
+            let _ = reply.send(manager.start_service(processes, &name));
+        }
+        Command::StopProcess { name, reply } => {
+            let _ = reply.send(manager.stop_process(processes, &name));
+        }
+        Command::RestartProcess { name, reply } => {
+            let _ = reply.send(manager.restart_process(processes, &name));
+        }
+        Command::Heartbeat { reply } => {
+            *restart_attempts = 0;
+            let _ = reply.send(manager.wake_from_idle(processes));
+        }
+        Command::Exited { pid, exit, uptime } => {
+            let backoff = manager.handle_exit(processes, restart_attempts, pid, exit, uptime);
+            if let (Some(delay), Ok(supervisor)) = (backoff, manager.supervisor()) {
+                supervisor.notify_after(delay, Command::Replace);
+            }
+        }
+        Command::Ready { pid } => manager.mark_ready(processes, pid),
+        Command::StartupFailed { pid, reason } => manager.fail_startup(processes, pid, reason),
+        Command::Respawn { pid } => manager.respawn(processes, pid),
+        Command::Kill { pid } => manager.kill_wedged(processes, pid),
+        // The app may have shut down, or a start replaced the backend, during the backoff
+        Command::Replace => manager.spawn_replacement(processes),
+        Command::ResetRestarts => *restart_attempts = 0,
+        #[cfg(test)]
+        Command::Run(f) => f(processes),
+        command @ (Command::ForceStop { .. } | Command::Status { .. }) => {
+            execute_unqueued(manager, command)
+        }
+    }
+}
+
+/// Commands that work from the snapshot and never touch the registry itself
+fn execute_unqueued<R: Runtime>(manager: &BackendManager<R>, command: Command) {
+    match command {
+        Command::ForceStop { reply } => {
+            let _ = reply.send(manager.force_stop());
+        }
+        Command::Status { reply } => {
+            let _ = reply.send(Ok(manager.status()));
+        }
+        command => reject(command),
+    }
+}
+
+/// Answer `command` with `error`
+fn fail(command: Command, error: BackendError) {
+    match command {
+        Command::Start { reply, .. }
+        | Command::Restart { reply }
+        | Command::Resync { reply, .. }
+        | Command::RestartProcess { reply, .. }
+        | Command::ForceStop { reply }
+        | Command::Heartbeat { reply } => {
+            let _ = reply.send(Err(error));
+        }
+        Command::StartBackend { reply }
+        | Command::StartProcess { reply, .. }
+        | Command::StartService { reply, .. }
+        | Command::StopProcess { reply, .. } => {
+            let _ = reply.send(Err(error));
+        }
+        Command::Stop { reply } | Command::Shutdown { reply } => {
+            let _ = reply.send(Err(error));
+        }
+        Command::Reload { reply } => {
+            let _ = reply.send(Err(error));
+        }
+        Command::Status { reply } => {
+            let _ = reply.send(Err(error));
+        }
+        // Events have nobody waiting for an answer
+        Command::Exited { .. }
+        | Command::Ready { .. }
+        | Command::StartupFailed { .. }
+        | Command::Respawn { .. }
+        | Command::Kill { .. }
+        | Command::Replace
+        | Command::ResetRestarts => {}
+        #[cfg(test)]
+        Command::Run(_) => {}
+    }
+}
+
+fn reject(command: Command) {
+    fail(command, BackendError::ManagerUnavailable);
+}
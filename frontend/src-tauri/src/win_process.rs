@@ -0,0 +1,149 @@
+//! Native queries of other processes on Windows.
+//!
+//! These used to shell out to tasklist and PowerShell, which takes hundreds of
+//! milliseconds per call and fails on machines where PowerShell is locked down.
+//! The process table is read every 100 ms while a tree is stopped, which a
+//! ToolHelp snapshot keeps cheap.
+
+use std::io;
+use std::ptr;
+
+use windows_sys::Wdk::System::Threading::{
+    NtQueryInformationProcess, ProcessCommandLineInformation,
+};
+use windows_sys::Win32::Foundation::{
+    CloseHandle, ERROR_INVALID_PARAMETER, HANDLE, INVALID_HANDLE_VALUE, STILL_ACTIVE,
+    UNICODE_STRING,
+};
+use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+    CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+};
+use windows_sys::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+use windows_sys::Win32::System::Threading::{
+    GetExitCodeProcess, OpenProcess, PROCESS_ACCESS_RIGHTS, PROCESS_QUERY_LIMITED_INFORMATION,
+    PROCESS_VM_READ,
+};
+
+/// Process or snapshot handle, closed on drop
+struct Handle(HANDLE);
+
+impl Handle {
+    /// Open `pid` with `access`; an error of `ERROR_INVALID_PARAMETER` means no
+    /// process has that PID
+    fn open(pid: u32, access: PROCESS_ACCESS_RIGHTS) -> io::Result<Self> {
+        // SAFETY: plain FFI call, the returned handle is checked and owned below
+        let handle = unsafe { OpenProcess(access, 0, pid) };
+        if handle.is_null() {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self(handle))
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        // SAFETY: the handle is owned by this value and closed exactly once
+        unsafe { CloseHandle(self.0) };
+    }
+}
+
+/// Whether `pid` is running; assumed so when the probe can't run
+pub fn process_alive(pid: u32) -> bool {
+    let process = match Handle::open(pid, PROCESS_QUERY_LIMITED_INFORMATION) {
+        Ok(process) => process,
+        Err(e) if e.raw_os_error() == Some(ERROR_INVALID_PARAMETER as i32) => return false,
+        // Access denied to a protected process still means it exists
+        Err(_) => return true,
+    };
+    let mut code = 0;
+    // SAFETY: `process` is open and `code` is a valid output
+    if unsafe { GetExitCodeProcess(process.0, &mut code) } == 0 {
+        return true;
+    }
+    // A process that exited with code 259 is mistaken for running, as with any
+    // caller of GetExitCodeProcess
+    code != STILL_ACTIVE as u32
+}
+
+/// Full command line of `pid`, or `None` when it isn't running or can't be inspected
+pub fn command_line(pid: u32) -> Option<String> {
+    let process = Handle::open(pid, PROCESS_QUERY_LIMITED_INFORMATION).ok()?;
+    let mut needed = 0;
+    // SAFETY: a query with an empty buffer only reports the size it needs
+    unsafe {
+        NtQueryInformationProcess(
+            process.0,
+            ProcessCommandLineInformation,
+            ptr::null_mut(),
+            0,
+            &mut needed,
+        )
+    };
+    if needed == 0 {
+        return None;
+    }
+    // u64 elements keep the UNICODE_STRING at the start aligned
+    let mut buffer = vec![0u64; (needed as usize).div_ceil(size_of::<u64>())];
+    // SAFETY: the buffer holds at least `needed` bytes
+    let status = unsafe {
+        NtQueryInformationProcess(
+            process.0,
+            ProcessCommandLineInformation,
+            buffer.as_mut_ptr().cast(),
+            needed,
+            &mut needed,
+        )
+    };
+    if status < 0 {
+        return None;
+    }
+    // SAFETY: on success the buffer starts with a UNICODE_STRING whose text is
+    // stored in the buffer right after it
+    let text = unsafe {
+        let header = &*buffer.as_ptr().cast::<UNICODE_STRING>();
+        if header.Buffer.is_null() {
+            return None;
+        }
+        std::slice::from_raw_parts(header.Buffer, usize::from(header.Length) / 2)
+    };
+    let line = String::from_utf16_lossy(text).trim().to_string();
+    (!line.is_empty()).then_some(line)
+}
+
+/// `pid ppid` of every process
+pub fn processes() -> Option<Vec<(u32, u32)>> {
+    // SAFETY: plain FFI call, the returned handle is checked and owned below
+    let snapshot = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+    if snapshot == INVALID_HANDLE_VALUE {
+        return None;
+    }
+    let snapshot = Handle(snapshot);
+    let mut entry = PROCESSENTRY32W {
+        dwSize: size_of::<PROCESSENTRY32W>() as u32,
+        ..Default::default()
+    };
+    let mut rows = Vec::new();
+    // SAFETY: the snapshot is open and `entry` has its size set
+    let mut more = unsafe { Process32FirstW(snapshot.0, &mut entry) } != 0;
+    while more {
+        rows.push((entry.th32ProcessID, entry.th32ParentProcessID));
+        // SAFETY: as above
+        more = unsafe { Process32NextW(snapshot.0, &mut entry) } != 0;
+    }
+    Some(rows)
+}
+
+/// Working set of `pid` in bytes, `None` when it can't be opened
+pub fn working_set(pid: u32) -> Option<u64> {
+    let process = Handle::open(pid, PROCESS_QUERY_LIMITED_INFORMATION | PROCESS_VM_READ).ok()?;
+    let size = size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+    let mut counters = PROCESS_MEMORY_COUNTERS {
+        cb: size,
+        ..Default::default()
+    };
+    // SAFETY: `process` is open and `counters` is as large as `size` says
+    if unsafe { GetProcessMemoryInfo(process.0, &mut counters, size) } == 0 {
+        return None;
+    }
+    Some(counters.WorkingSetSize as u64)
+}